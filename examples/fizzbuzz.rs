@@ -1,4 +1,7 @@
-use std::io::{stdin, Read};
+use std::{
+    env,
+    io::{stdin, Read},
+};
 use yap_streaming::{StreamTokens, Tokens};
 
 /// Parses a line ending of either "\r\n" (windows) or "\n" (linux)
@@ -20,6 +23,8 @@ enum FizzBuzz {
 }
 
 /// Converts number to appropriate fizzbuzz type.
+// `is_multiple_of` would need Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
 fn fizzbuzz(x: u32) -> FizzBuzz {
     if x % 5 == 0 && x % 3 == 0 {
         FizzBuzz::Both
@@ -34,8 +39,12 @@ fn fizzbuzz(x: u32) -> FizzBuzz {
 
 /// An example program that parses stdin for numbers that match an output by the rules of fizzbuzz.
 /// It prints as it parses so the incremental parsing is more obvious.
+///
+/// Pass `--no-echo` to skip printing the input back at the end.
+/// Nothing then needs to rewind to the start, so memory use stays bounded however much input is entered.
 fn main() {
-    let stdin = stdin().bytes().map(Result::unwrap);
+    let echo = !env::args().any(|arg| arg == "--no-echo");
+    let stdin = stdin().lock().bytes().map(Result::unwrap);
     // Can't use `stdin.clone()` because it is over a stream of values that are not in memory.
     // If we could clone then one of the other yap token types would be preferable.
     let mut tokens = StreamTokens::new(stdin);
@@ -47,23 +56,25 @@ if it is divisible by five I'll say \"buzz\", and if it is divisible by both 3 a
     // Set a location to allow rewinding back to this point later.
     // All items since the oldest location (`start` in this case) will be buffered
     // so that a rewind to this point can occur with [`Tokens::set_location`] if needed.
-    let start = tokens.location();
+    // Without it only the number currently being parsed is buffered.
+    let start = echo.then(|| tokens.location());
+    let mut longest_number = 0;
 
     // Demonstrate streaming parsing of input.
     // This is relatively painless and looks the same as a non-streaming parser.
-    // `StreamTokens` handles buffering and `Bytes<Stdin>` handles blocking in this case.
+    // `StreamTokens` handles buffering and `Bytes<StdinLock>` handles blocking in this case.
     // The general form is some iterator over new input and wrapping that in `StreamTokens`
     // to make that stream of items parseable.
     for num in tokens
         .sep_by(
             |t| {
-                Some(
-                    t.take_while(u8::is_ascii_digit)
-                        .into_iter()
-                        .map(|x| x as char)
-                        .collect::<String>()
-                        .parse::<u32>(),
-                )
+                let digits = t
+                    .take_while(u8::is_ascii_digit)
+                    .into_iter()
+                    .map(|x| x as char)
+                    .collect::<String>();
+                longest_number = longest_number.max(digits.len());
+                Some(digits.parse::<u32>())
             },
             |t| line_ending(t).is_some(),
         )
@@ -79,12 +90,18 @@ if it is divisible by five I'll say \"buzz\", and if it is divisible by both 3 a
         }
     }
 
-    // Can use location to reset to previous values because they have been internally buffered
-    let previous_tokens = tokens
-        .slice(start, tokens.location())
-        .as_iter()
-        .map(|x| x as char)
-        .collect::<String>();
-    println!("You entered:\n{previous_tokens}");
+    if let Some(start) = start {
+        // Can use location to reset to previous values because they have been internally buffered
+        let previous_tokens = tokens
+            .slice(start, tokens.location())
+            .as_iter()
+            .map(|x| x as char)
+            .collect::<String>();
+        println!("You entered:\n{previous_tokens}");
+    } else {
+        // Each number and the line ending after it are the most that was ever needed at once.
+        assert!(tokens.peak_buffer_len() <= longest_number + "\r\n".len());
+        println!("At most {} bytes were buffered", tokens.peak_buffer_len());
+    }
     println!("This parsed as: {parsed_result:?}");
 }
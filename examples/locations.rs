@@ -0,0 +1,37 @@
+//! Times creating many locations, comparing the constant time path for a parser moving forward
+//! with creating them out of order.
+//!
+//! Run with `cargo run --release --example locations`.
+use std::time::Instant;
+use yap_streaming::{StreamTokens, Tokens};
+
+const N: usize = 100_000;
+
+fn main() {
+    let input = vec![0u8; N];
+
+    // A location per token, like a span-heavy lexer.
+    let mut tokens = StreamTokens::new(input.iter().copied());
+    let start = Instant::now();
+    let mut locations = Vec::with_capacity(N);
+    while tokens.peek().is_some() {
+        locations.push(tokens.location());
+        tokens.next();
+    }
+    let forward = start.elapsed();
+    drop(locations);
+
+    // Cloning the oldest location each time inserts before every other one.
+    let mut tokens = StreamTokens::new(input.iter().copied());
+    let oldest = tokens.location();
+    let mut locations = Vec::with_capacity(N);
+    let start = Instant::now();
+    while tokens.next().is_some() {
+        locations.push(tokens.location());
+        locations.push(oldest.clone());
+    }
+    let out_of_order = start.elapsed();
+
+    println!("{N} locations at increasing offsets: {forward:?}");
+    println!("{N} locations at increasing offsets interleaved with clones of the oldest: {out_of_order:?}");
+}
@@ -158,8 +158,35 @@ assert!(io_err.is_none());
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+mod macros;
 #[cfg(feature = "alloc")]
 mod stream_tokens;
+#[cfg(all(feature = "alloc", feature = "serde"))]
+pub use stream_tokens::serde::SerializableLocation;
 #[cfg(feature = "alloc")]
-pub use stream_tokens::{str_stream_tokens::StrStreamTokens, StreamTokens, StreamTokensLocation};
+pub use stream_tokens::{
+    balanced::BalanceError,
+    bytes::{Bom, ParseUtf8Error},
+    captured_tokens::{CapturedTokens, CapturedTokensLocation},
+    checked::ChecksumError,
+    coalesce::CoalesceTokens,
+    end_reason::EndReason,
+    frozen::{FrozenTokens, FrozenTokensLocation},
+    inspect::InspectTokens,
+    length_prefixed::{LengthPrefixError, LengthPrefixed},
+    metrics::StreamMetrics,
+    newlines::NormalizedTokens,
+    sequence::SequenceMismatch,
+    str_stream_tokens::{
+        base64::Base64Error, class::CharClass, csv::CsvError, fixed_width::FixedWidthError,
+        hex::HexError, int::IntParseError, ip::IpParseError, quoted::QuoteError,
+        trivia::TriviaError, StrStreamTokens,
+    },
+    sub_parser::{SubStreamTokens, SubStreamTokensLocation},
+    trie::KeywordTrie,
+    ws_skipping::WsSkipping,
+    StreamTokens, StreamTokensAnchor, StreamTokensLocation,
+};
+#[cfg(all(feature = "alloc", feature = "testing"))]
+pub mod testing;
 pub use yap::{IntoTokens, TokenLocation, Tokens};
@@ -0,0 +1,89 @@
+/// Run a fixed sequence of field parsers, returning a tuple of their outputs.
+///
+/// Each field is a closure taking the tokens and returning an [`Option`].
+/// If a field returns [`None`] the location is reset to before the first field,
+/// and the zero-based index of the field that failed is returned as the error.
+///
+/// # Example
+///
+/// ```rust
+/// use yap_streaming::{parse_fields, StreamTokens, Tokens};
+///
+/// let mut tokens = StreamTokens::new([0x34, 0x12, 7, 0xff].into_iter());
+/// let header = parse_fields!(
+///     &mut tokens,
+///     |t: &mut StreamTokens<_, _>| Some(u16::from_le_bytes([t.next()?, t.next()?])),
+///     |t: &mut StreamTokens<_, _>| t.next(),
+/// );
+/// assert_eq!(header, Ok((0x1234, 7)));
+/// assert_eq!(tokens.next(), Some(0xff));
+/// ```
+#[macro_export]
+macro_rules! parse_fields {
+    ($tokens:expr, $($field:expr),+ $(,)?) => {{
+        let tokens = $tokens;
+        let from = $crate::Tokens::location(tokens);
+        let mut field: usize = 0;
+        let res = 'fields: {
+            ::core::result::Result::Ok(($({
+                field += 1;
+                match ($field)(&mut *tokens) {
+                    ::core::option::Option::Some(out) => out,
+                    ::core::option::Option::None => break 'fields ::core::result::Result::Err(field - 1),
+                }
+            },)+))
+        };
+        if res.is_err() {
+            $crate::Tokens::set_location(tokens, from);
+        }
+        res
+    }};
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::{StreamTokens, Tokens};
+    use alloc::{string::String, vec::Vec};
+
+    type ByteTokens<'a> = StreamTokens<core::iter::Copied<core::slice::Iter<'a, u8>>, Vec<u8>>;
+
+    fn u16_field(t: &mut ByteTokens<'_>) -> Option<u16> {
+        Some(u16::from_le_bytes([t.next()?, t.next()?]))
+    }
+
+    fn name_field(t: &mut ByteTokens<'_>) -> Option<String> {
+        let len = t.next()?.into();
+        let name = t.take(len).collect::<Vec<_>>();
+        if name.len() != len {
+            return None;
+        }
+        String::from_utf8(name).ok()
+    }
+
+    #[test]
+    fn parse_fields_header() {
+        let mut tokens = ByteTokens::new_with_buffer(b"\x01\x00\x02\x03abc!".iter().copied());
+        let header = parse_fields!(
+            &mut tokens,
+            u16_field,
+            |t: &mut ByteTokens| t.next(),
+            name_field
+        );
+        assert_eq!(header, Ok((1, 2, String::from("abc"))));
+        assert_eq!(tokens.next(), Some(b'!'));
+    }
+
+    #[test]
+    fn parse_fields_rewinds_on_failure() {
+        let input = b"\x01\x00\x02\x05ab";
+        let mut tokens = ByteTokens::new_with_buffer(input.iter().copied());
+        let header = parse_fields!(
+            &mut tokens,
+            u16_field,
+            |t: &mut ByteTokens| t.next(),
+            name_field
+        );
+        assert_eq!(header, Err(2));
+        assert_eq!(tokens.collect::<Vec<_>>(), input);
+    }
+}
@@ -1,12 +1,38 @@
-use alloc::{collections::VecDeque, rc::Rc, vec::Vec};
+#[cfg(doc)]
+use alloc::string::String;
+use alloc::{boxed::Box, collections::VecDeque, rc::Rc, vec::Vec};
 use core::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     fmt::Debug,
-    iter::{Fuse, Iterator},
+    iter::{FromFn, Iterator},
 };
+use rolling_hash::RollingHash;
 use yap::{IntoTokens, TokenLocation, Tokens};
 
+pub(crate) mod balanced;
+pub(crate) mod bytes;
+pub(crate) mod captured_tokens;
+pub(crate) mod checked;
+pub(crate) mod coalesce;
+mod combinators;
+pub(crate) mod end_reason;
+pub(crate) mod frozen;
+pub(crate) mod inspect;
+#[cfg(feature = "std")]
+mod io;
+pub(crate) mod length_prefixed;
+pub(crate) mod metrics;
+pub(crate) mod newlines;
+#[cfg(feature = "nom")]
+mod nom;
+mod rolling_hash;
+pub(crate) mod sequence;
+#[cfg(feature = "serde")]
+pub(crate) mod serde;
 pub(crate) mod str_stream_tokens;
+pub(crate) mod sub_parser;
+pub(crate) mod trie;
+pub(crate) mod ws_skipping;
 
 /// Helper trait for defining buffers that can be used to store items in [`StreamTokens`] for [`Tokens::set_location()`] resets
 pub trait StreamTokensBuffer<Item>: Default {
@@ -16,6 +42,35 @@ pub trait StreamTokensBuffer<Item>: Default {
     fn push(&mut self, item: Item);
     /// Get the item at the given `idx` if it exists.
     fn get(&self, idx: usize) -> Option<Item>;
+    /// Number of items in the buffer.
+    fn len(&self) -> usize;
+    /// Whether the buffer has no items.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Make room for at least `additional` more items without reallocating, if the buffer supports it.
+    ///
+    /// Defaults to doing nothing.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+    /// Free capacity beyond what `min_capacity` items need, if the buffer supports it.
+    ///
+    /// Defaults to doing nothing.
+    fn shrink_to(&mut self, min_capacity: usize) {
+        let _ = min_capacity;
+    }
+    /// Add `items` to the back of the buffer in order.
+    ///
+    /// Defaults to a [`Self::push`] per item.
+    fn push_many(&mut self, items: &[Item])
+    where
+        Item: Clone,
+    {
+        for item in items {
+            self.push(item.clone());
+        }
+    }
 }
 
 impl<Item: core::clone::Clone> StreamTokensBuffer<Item> for VecDeque<Item> {
@@ -37,16 +92,184 @@ impl<Item: core::clone::Clone> StreamTokensBuffer<Item> for VecDeque<Item> {
     fn get(&self, idx: usize) -> Option<Item> {
         self.get(idx).cloned()
     }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn push_many(&mut self, items: &[Item]) {
+        self.extend(items.iter().cloned());
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+
+    fn shrink_to(&mut self, min_capacity: usize) {
+        self.shrink_to(min_capacity);
+    }
+}
+
+/// Dropping `n` items from the front of a [`Vec`] shifts all the items left after them, where a [`VecDeque`] only pays for the `n` dropped,
+/// but a [`Vec`] keeps items contiguous and derefs to a slice of them.
+impl<Item: core::clone::Clone> StreamTokensBuffer<Item> for Vec<Item> {
+    fn drain_front(&mut self, n: usize) {
+        if n >= self.len() {
+            self.clear();
+        } else {
+            self.drain(..n);
+        }
+    }
+
+    fn push(&mut self, item: Item) {
+        self.push(item);
+    }
+
+    fn get(&self, idx: usize) -> Option<Item> {
+        self.as_slice().get(idx).cloned()
+    }
+
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn push_many(&mut self, items: &[Item]) {
+        self.extend_from_slice(items);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+
+    fn shrink_to(&mut self, min_capacity: usize) {
+        self.shrink_to(min_capacity);
+    }
+}
+
+/// Called with the offset of the first item and the buffer before that many items are drained from its front.
+#[allow(clippy::type_complexity)]
+struct DrainHook<Buf>(Box<dyn FnMut(usize, &Buf, usize)>);
+
+impl<Buf> Debug for DrainHook<Buf> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("DrainHook")
+    }
 }
 
+/// Capacity [`StreamTokens::set_auto_commit`] leaves the buffer with when nothing needs to be buffered.
+const AUTO_COMMIT_CAPACITY: usize = 64;
+
 /// Buffer over items of an iterator.
-#[derive(Clone, Default, Debug, PartialEq, Eq)]
+#[derive(Default, Debug)]
 struct Buffer<Buf> {
     oldest_elem_cursor: usize,
     elements: Buf,
+    /// Number of items in `elements`, tracked here because [`StreamTokensBuffer::len`] may not be `O(1)`.
+    len: usize,
+    /// Most items `elements` may hold before the oldest are dropped regardless of live locations.
+    max_len: Option<usize>,
+    /// Most items `elements` has held at once.
+    peak_len: usize,
+    /// See [`StreamTokens::set_auto_commit`].
+    auto_commit: bool,
+    /// See [`StreamTokens::on_drain`].
+    on_drain: Option<DrainHook<Buf>>,
+}
+
+impl<Buf> Buffer<Buf> {
+    /// Drop all items before `cursor`.
+    fn drain_to<Item>(&mut self, cursor: usize)
+    where
+        Buf: StreamTokensBuffer<Item>,
+    {
+        let delta = cursor.saturating_sub(self.oldest_elem_cursor);
+        if let Some(DrainHook(f)) = &mut self.on_drain {
+            let drained = delta.min(self.len);
+            if drained > 0 {
+                f(self.oldest_elem_cursor, &self.elements, drained);
+            }
+        }
+        self.elements.drain_front(delta);
+        self.len = self.len.saturating_sub(delta);
+        self.oldest_elem_cursor += delta;
+    }
+
+    /// Add an item to the back, dropping from the front if that exceeds `max_len`.
+    fn push<Item>(&mut self, item: Item)
+    where
+        Buf: StreamTokensBuffer<Item>,
+    {
+        self.elements.push(item);
+        self.len += 1;
+        if let Some(max_len) = self.max_len {
+            if self.len > max_len {
+                self.drain_to(self.oldest_elem_cursor + self.len - max_len);
+            }
+        }
+        self.peak_len = self.peak_len.max(self.len);
+    }
+}
+
+/// The wrapped iterator, which is [`Fuse`](core::iter::Fuse)d unless `fused` is turned off with [`StreamTokens::with_fused`].
+struct Source<I: Iterator> {
+    iter: I,
+    fused: bool,
+    /// Whether `iter` has returned [`None`].
+    exhausted: bool,
+    /// See [`StreamTokens::start_recording`].
+    recorded: Option<Vec<I::Item>>,
+    /// How many of the next items were already recorded before being fed back into `iter`.
+    unrecorded: usize,
+}
+
+impl<I: Iterator + Debug> Debug for Source<I> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Source")
+            .field("iter", &self.iter)
+            .field("fused", &self.fused)
+            .field("exhausted", &self.exhausted)
+            .field("recorded", &self.recorded.as_ref().map(Vec::len))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I: Iterator> Source<I> {
+    fn new(iter: I) -> Self {
+        Self {
+            iter,
+            fused: true,
+            exhausted: false,
+            recorded: None,
+            unrecorded: 0,
+        }
+    }
+}
+
+impl<I> Iterator for Source<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.fused && self.exhausted {
+            return None;
+        }
+        let next = self.iter.next();
+        self.exhausted = next.is_none();
+        if let Some(item) = &next {
+            if self.unrecorded > 0 {
+                self.unrecorded -= 1;
+            } else if let Some(recorded) = &mut self.recorded {
+                recorded.push(item.clone());
+            }
+        }
+        next
+    }
 }
 
-/// Enables parsing a stream of values from a [`Fuse`]d iterator that can't itself be cloned.
+/// Enables parsing a stream of values from an iterator that can't itself be cloned.
 /// In order to be able to rewind the iterator it must save values since the oldest not [`Drop`]ed [`StreamTokensLocation`] into `Buf`.
 ///
 /// See [`Self::new`] for example usage.
@@ -55,13 +278,26 @@ pub struct StreamTokens<I, Buf>
 where
     I: Iterator,
 {
-    iter: Fuse<I>,
+    iter: Source<I>,
     cursor: usize,
     buffer: Buffer<Buf>,
     /// Sorted list of the oldest items needed per live location
     checkout: Rc<RefCell<Vec<usize>>>,
+    /// How many of the entries in `checkout` belong to a [`StreamTokensAnchor`]
+    anchors: Rc<Cell<usize>>,
+    /// See [`StreamTokens::max_location_depth`].
+    max_depth: Cell<usize>,
+    /// See [`StreamTokens::enable_rolling_hash`].
+    rolling_hash: Option<RollingHash<I>>,
+    /// See [`StreamTokens::labeled_location`].
+    #[cfg(debug_assertions)]
+    labels: Labels,
 }
 
+/// Cursor and label of each live location from [`StreamTokens::labeled_location`].
+#[cfg(debug_assertions)]
+type Labels = Rc<RefCell<Vec<(usize, &'static str)>>>;
+
 /// This implements [`TokenLocation`] and stores the location. It also marks the [`Iterator::Item`]s
 /// since it was created to be stored for when the corresponding [`StreamTokens`] is reset.
 ///
@@ -69,24 +305,41 @@ where
 ///
 /// The [`Drop`] implementation will un-mark that [`Iterator::Item`]s must be stored,
 /// allowing the originating [`StreamTokens`] to drop old values and free memory.
+///
+/// Creating or cloning a location at or after every live one, as a parser moving forward does,
+/// and dropping the newest location first take constant time.
+/// Otherwise they take time linear in the number of live locations.
 #[derive(Debug)]
 pub struct StreamTokensLocation {
     cursor: usize,
     checkout: Rc<RefCell<Vec<usize>>>,
+    /// Set for locations from [`StreamTokens::labeled_location`].
+    #[cfg(debug_assertions)]
+    label: Option<(&'static str, Labels)>,
 }
 
 impl Clone for StreamTokensLocation {
     fn clone(&self) -> Self {
         // Checkout the cursor's position again
         let mut checkout = self.checkout.borrow_mut();
-        let idx = match checkout.binary_search(&self.cursor) {
-            Ok(x) | Err(x) => x,
-        };
-        checkout.insert(idx, self.cursor);
+        if checkout.last() == Some(&self.cursor) {
+            checkout.push(self.cursor);
+        } else {
+            let idx = match checkout.binary_search(&self.cursor) {
+                Ok(x) | Err(x) => x,
+            };
+            checkout.insert(idx, self.cursor);
+        }
+        #[cfg(debug_assertions)]
+        if let Some((label, labels)) = &self.label {
+            labels.borrow_mut().push((self.cursor, label));
+        }
         // Then copy
         Self {
             cursor: self.cursor,
             checkout: Rc::clone(&self.checkout),
+            #[cfg(debug_assertions)]
+            label: self.label.clone(),
         }
     }
 }
@@ -100,7 +353,22 @@ impl Eq for StreamTokensLocation {}
 
 impl Drop for StreamTokensLocation {
     fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        if let Some((label, labels)) = &self.label {
+            let mut labels = labels.borrow_mut();
+            if let Some(idx) = labels
+                .iter()
+                .position(|&entry| entry == (self.cursor, *label))
+            {
+                labels.swap_remove(idx);
+            }
+        }
         let mut checkout = self.checkout.borrow_mut();
+        // Locations are usually dropped newest first.
+        if checkout.last() == Some(&self.cursor) {
+            checkout.pop();
+            return;
+        }
         // Remove self.cursor from checkout.
         let idx = checkout
             .binary_search(&self.cursor)
@@ -109,25 +377,165 @@ impl Drop for StreamTokensLocation {
     }
 }
 
+impl StreamTokensLocation {
+    /// Create a location at `cursor`, marking the items after it as needed in `checkout`.
+    fn checked_out(checkout: &Rc<RefCell<Vec<usize>>>, cursor: usize) -> Self {
+        let mut entries = checkout.borrow_mut();
+        // The cursor is usually at or past every other location.
+        if entries.last().is_none_or(|&last| last <= cursor) {
+            entries.push(cursor);
+        } else {
+            match entries.binary_search(&cursor) {
+                Ok(x) | Err(x) => entries.insert(x, cursor),
+            };
+        }
+        StreamTokensLocation {
+            cursor,
+            checkout: Rc::clone(checkout),
+            #[cfg(debug_assertions)]
+            label: None,
+        }
+    }
+
+    /// Whether `tokens` can still [`Tokens::set_location()`] to this location.
+    ///
+    /// This is always `true` for the [`StreamTokens`] this location came from unless it was created with a bounded buffer such as [`StreamTokens::new_bounded`],
+    /// since a live location keeps its items buffered otherwise. An [acknowledged](StreamTokensAnchor::acknowledge) anchor does not,
+    /// which is why [`StreamTokensAnchor::location`] can return [`None`] instead.
+    pub fn is_valid<I: Iterator, Buf>(&self, tokens: &StreamTokens<I, Buf>) -> bool {
+        self.cursor >= tokens.buffer.oldest_elem_cursor
+    }
+}
+
 impl TokenLocation for StreamTokensLocation {
     fn offset(&self) -> usize {
         self.cursor
     }
 }
 
+/// A long-lived [`StreamTokensLocation`], such as the start of a record, created with [`StreamTokens::anchor`].
+///
+/// Like a location it keeps the [`Iterator::Item`]s since it was created buffered until it is [`Drop`]ed,
+/// but it is counted separately from transient locations by [`StreamTokens::live_locations`].
+/// Once [`Self::acknowledge`]d it stops keeping them buffered, so committing such as with
+/// [`StreamTokens::drain_consumed`] or [`StreamTokens::set_auto_commit`] ignores it.
+#[derive(Debug)]
+pub struct StreamTokensAnchor {
+    cursor: usize,
+    /// [`None`] once acknowledged.
+    location: Option<StreamTokensLocation>,
+    anchors: Rc<Cell<usize>>,
+}
+
+impl StreamTokensAnchor {
+    /// Get a location at this anchor in the `tokens` it came from, for example to [`Tokens::set_location()`] to it.
+    ///
+    /// Returns [`None`] if the items since it are no longer buffered, which can happen once it is [acknowledged](Self::acknowledge)
+    /// or with a bounded buffer such as [`StreamTokens::new_bounded`].
+    pub fn location<I: Iterator, Buf>(
+        &self,
+        tokens: &StreamTokens<I, Buf>,
+    ) -> Option<StreamTokensLocation> {
+        (self.cursor >= tokens.buffer.oldest_elem_cursor)
+            .then(|| StreamTokensLocation::checked_out(&tokens.checkout, self.cursor))
+    }
+
+    /// Stop keeping the items since this anchor buffered, while still being able to ask for its [`Self::location`] until they are dropped.
+    ///
+    /// It then no longer counts towards [`StreamTokens::live_anchors`].
+    /// Acknowledging an anchor twice does nothing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("key=value".chars());
+    /// let mut record_start = tokens.anchor();
+    /// tokens.take_while(|&c| c != '=').consume();
+    /// assert_eq!(tokens.drain_consumed().count(), 0);
+    ///
+    /// record_start.acknowledge();
+    /// assert_eq!(tokens.drain_consumed().collect::<String>(), "key");
+    /// assert_eq!(record_start.location(&tokens), None);
+    /// ```
+    pub fn acknowledge(&mut self) {
+        if self.location.take().is_some() {
+            self.anchors.set(self.anchors.get() - 1);
+        }
+    }
+
+    /// Whether [`Self::acknowledge`] was called on this anchor or the one it was cloned from.
+    pub fn is_acknowledged(&self) -> bool {
+        self.location.is_none()
+    }
+}
+
+impl Clone for StreamTokensAnchor {
+    fn clone(&self) -> Self {
+        if self.location.is_some() {
+            self.anchors.set(self.anchors.get() + 1);
+        }
+        Self {
+            cursor: self.cursor,
+            location: self.location.clone(),
+            anchors: Rc::clone(&self.anchors),
+        }
+    }
+}
+
+impl PartialEq for StreamTokensAnchor {
+    fn eq(&self, other: &Self) -> bool {
+        self.cursor == other.cursor
+    }
+}
+impl Eq for StreamTokensAnchor {}
+
+impl Drop for StreamTokensAnchor {
+    fn drop(&mut self) {
+        self.acknowledge();
+    }
+}
+
+impl TokenLocation for StreamTokensAnchor {
+    fn offset(&self) -> usize {
+        self.cursor
+    }
+}
+
 impl<I: Iterator, Buf: Default> StreamTokens<I, Buf> {
     /// Generic new function allowing arbitrary buffer.
     /// Exists because type inference is not smart enough to try the default generic when calling [`Self::new`] so `new` hardcodes the default.
     /// See <https://faultlore.com/blah/defaults-affect-inference/#default-type-parameters>
     pub(crate) fn _new(iter: I) -> Self {
         StreamTokens {
-            // Store a fused iterator so the buffer can safely be of `Item` instead of `Option<Item>`
-            iter: iter.fuse(),
+            // A `None` is never buffered, so the buffer can be of `Item` instead of `Option<Item>` either way.
+            iter: Source::new(iter),
             cursor: Default::default(),
             buffer: Default::default(),
             checkout: Default::default(),
+            anchors: Default::default(),
+            max_depth: Default::default(),
+            rolling_hash: None,
+            #[cfg(debug_assertions)]
+            labels: Default::default(),
         }
     }
+
+    /// Like [`StreamTokens::new`] but buffering items in the type given by the annotation,
+    /// which can be a [`VecDeque`], a [`Vec`], or a [`String`] for [`char`]s.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::<_, Vec<char>>::new_with_buffer("hello".chars());
+    /// assert!(tokens.tokens("hello".chars()));
+    /// ```
+    pub fn new_with_buffer(iter: I) -> Self {
+        Self::_new(iter)
+    }
 }
 
 impl<I: Iterator> StreamTokens<I, VecDeque<I::Item>>
@@ -155,104 +563,806 @@ where
     pub fn new(iter: I) -> Self {
         Self::_new(iter)
     }
-}
-
-impl<I, Buffer> Tokens for StreamTokens<I, Buffer>
-where
-    I: Iterator,
-    I::Item: Clone,
-    Buffer: StreamTokensBuffer<I::Item>,
-{
-    type Item = I::Item;
-
-    type Location = StreamTokensLocation;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.cursor += 1;
-
-        // Try buffer
-        {
-            // If buffer has needed element use buffer before getting new elements.
-            if let Some(val) = self
-                .buffer
-                .elements
-                .get(self.cursor - 1 - self.buffer.oldest_elem_cursor)
-            {
-                return Some(val);
-            }
-        }
 
-        let checkout = self.checkout.borrow();
-        // Clear buffer of old values
-        {
-            // Remove old values no longer needed by any location
-            let min = match checkout.first() {
-                Some(&x) => x.min(self.cursor),
-                None => self.cursor,
-            };
-            let delta = min - self.buffer.oldest_elem_cursor;
-            self.buffer.elements.drain_front(delta);
-            self.buffer.oldest_elem_cursor = min;
-        }
-
-        // Handle cache miss
-        {
-            let next = self.iter.next()?;
-            // Don't save to buffer if no locations exist which might need the value again
-            if checkout.is_empty() {
-                Some(next)
-            } else {
-                self.buffer.elements.push(next.clone());
-                Some(next)
-            }
-        }
+    /// Like [`Self::new`] but never buffers more than `max_buffer` items.
+    ///
+    /// Once the buffer is full the oldest items are dropped even if a [`StreamTokensLocation`] still needs them.
+    /// Rewind with [`Self::try_set_location`] when the target may have been dropped,
+    /// [`Tokens::set_location`] panics in that case.
+    /// Peeking one token ahead always works.
+    ///
+    /// # Panics
+    ///
+    /// If `max_buffer` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{StreamTokens, TokenLocation, Tokens};
+    ///
+    /// let mut tokens = StreamTokens::new_bounded("abcdef".chars(), 2);
+    ///
+    /// let start = tokens.location();
+    /// tokens.take(4).consume();
+    /// // Only "cd" is still buffered.
+    /// let start = tokens.try_set_location(start).unwrap_err();
+    /// assert_eq!(start.offset(), 0);
+    /// ```
+    pub fn new_bounded(iter: I, max_buffer: usize) -> Self {
+        assert!(max_buffer > 0, "bounded buffer must hold at least one item");
+        let mut tokens = Self::_new(iter);
+        tokens.buffer.max_len = Some(max_buffer);
+        tokens
     }
 
-    fn location(&self) -> Self::Location {
-        // Checkout value at current location
-        let mut checkout = self.checkout.borrow_mut();
-        match checkout.binary_search(&self.cursor) {
-            Ok(x) | Err(x) => checkout.insert(x, self.cursor),
-        };
-        StreamTokensLocation {
-            cursor: self.cursor,
-            checkout: Rc::clone(&self.checkout),
+    /// Like [`StreamTokens::new`], but the tokens start with the items of `prefix`, such as a header already read to sniff the format.
+    /// They are buffered like any other item, so locations can rewind into them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut file = b"\x89PNG rest".iter().copied();
+    /// let magic: Vec<u8> = file.by_ref().take(4).collect();
+    ///
+    /// let mut tokens = StreamTokens::with_prefix(magic, file);
+    /// assert!(tokens.tokens(b"\x89PNG".iter().copied()));
+    /// assert_eq!(tokens.next(), Some(b' '));
+    /// ```
+    pub fn with_prefix(prefix: impl IntoIterator<Item = I::Item>, iter: I) -> Self {
+        let mut tokens = Self::_new(iter);
+        for item in prefix {
+            tokens.buffer.push(item);
         }
+        tokens
     }
+}
 
-    fn set_location(&mut self, location: Self::Location) {
-        // Update cursor to new value
-        self.cursor = location.cursor;
-        // Location removes itself from checkout on drop
+impl<Item: Clone> StreamTokens<alloc::vec::IntoIter<Item>, VecDeque<Item>> {
+    /// Use this method to parse items already collected in memory, such as those from [`Self::stop_recording`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::from_vec(vec![1, 2, 3]);
+    /// assert!(tokens.tokens([1, 2]));
+    /// assert_eq!(tokens.next(), Some(3));
+    /// ```
+    pub fn from_vec(items: Vec<Item>) -> Self {
+        Self::new(items.into_iter())
     }
+}
 
-    fn is_at_location(&self, location: &Self::Location) -> bool {
-        self.cursor == location.cursor
+impl<Item, F> StreamTokens<FromFn<F>, VecDeque<Item>>
+where
+    Item: Clone,
+    F: FnMut() -> Option<Item>,
+{
+    /// Use this method to parse items pulled from a closure, such as a channel's `recv`,
+    /// instead of an [`Iterator`]. Parsing ends the first time `f` returns [`None`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::VecDeque;
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut source: VecDeque<char> = "hello world".chars().collect();
+    /// let mut tokens = StreamTokens::from_fn(|| source.pop_front());
+    ///
+    /// assert!(tokens.tokens("hello".chars()));
+    /// tokens.skip_while(|c| c.is_whitespace());
+    /// assert!(tokens.tokens("world".chars()));
+    /// assert_eq!(tokens.next(), None);
+    /// ```
+    pub fn from_fn(f: F) -> Self {
+        Self::new(core::iter::from_fn(f))
     }
 }
 
-impl<I, Buf> IntoTokens<I::Item> for StreamTokens<I, Buf>
+impl<I, Buf> StreamTokens<I, Buf>
 where
     I: Iterator,
-    I::Item: Clone + core::fmt::Debug,
+    I::Item: Clone,
     Buf: StreamTokensBuffer<I::Item>,
 {
-    type Tokens = Self;
-    fn into_tokens(self) -> Self {
-        self
-    }
-}
-
-#[cfg(test)]
+    /// Like [`Tokens::set_location()`] but gives the location back as an error
+    /// if the items after it are no longer buffered.
+    ///
+    /// This can only fail for streams created with a bounded buffer such as [`StreamTokens::new_bounded`],
+    /// since a live location keeps its items buffered otherwise.
+    pub fn try_set_location(
+        &mut self,
+        location: StreamTokensLocation,
+    ) -> Result<(), StreamTokensLocation> {
+        // Backtracking often restores to where it already is.
+        if self.cursor == location.cursor {
+            return Ok(());
+        }
+        if !location.is_valid(self) {
+            return Err(location);
+        }
+        self.cursor = location.cursor;
+        // Location removes itself from checkout on drop
+        Ok(())
+    }
+
+    /// Create a location at `cursor`, marking the items after it as needed.
+    fn checkout_at(&self, cursor: usize) -> StreamTokensLocation {
+        let location = StreamTokensLocation::checked_out(&self.checkout, cursor);
+        self.max_depth
+            .set(self.max_depth.get().max(self.checkout.borrow().len()));
+        location
+    }
+
+    /// Whether the wrapped iterator is treated as ended once it returns [`None`], which is the default.
+    ///
+    /// With `fused` set to `false` every [`Tokens::next()`] past the buffered items asks the wrapped iterator again,
+    /// so parsing can resume once a source that had no more items yet, such as a non-blocking reader, has more.
+    ///
+    /// For a source of [`Result`]s, map errors to [`None`] and keep them where the caller can see them.
+    /// After handling an error that only means "not yet", such as an `io::ErrorKind` of `WouldBlock`, `Interrupted` or `TimedOut`,
+    /// clear it and parse again. Other errors usually mean the source is done for good.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut pending = vec![Some('b'), None, Some('a')];
+    /// let mut tokens = StreamTokens::new(core::iter::from_fn(move || pending.pop().flatten()))
+    ///     .with_fused(false);
+    /// assert_eq!(tokens.next(), Some('a'));
+    /// assert_eq!(tokens.next(), None);
+    /// assert_eq!(tokens.next(), Some('b'));
+    /// ```
+    pub fn with_fused(mut self, fused: bool) -> Self {
+        self.iter.fused = fused;
+        self
+    }
+
+    /// Start recording every item pulled from the wrapped iterator, discarding anything recorded before.
+    ///
+    /// Items returned again from the buffer after rewinding are not recorded again, so replaying
+    /// what [`Self::stop_recording`] returns with [`StreamTokens::from_vec`] gives the same stream.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("ab".chars());
+    /// tokens.start_recording();
+    /// let start = tokens.location();
+    /// tokens.next();
+    /// tokens.set_location(start);
+    /// tokens.consume();
+    /// assert_eq!(tokens.stop_recording(), ['a', 'b']);
+    /// ```
+    pub fn start_recording(&mut self) {
+        self.iter.recorded = Some(Vec::new());
+    }
+
+    /// Stop recording and return the items pulled from the wrapped iterator since [`Self::start_recording`],
+    /// or nothing if it wasn't called.
+    pub fn stop_recording(&mut self) -> Vec<I::Item> {
+        self.iter.recorded.take().unwrap_or_default()
+    }
+
+    /// Number of items currently buffered so that live locations can be rewound to.
+    pub fn buffer_len(&self) -> usize {
+        self.buffer.len
+    }
+
+    /// Whether to drop consumed items from the buffer, and free its spare capacity, after every [`Tokens::next()`]
+    /// while there are no live locations. Off by default.
+    ///
+    /// Consumed items are otherwise only dropped the next time an item is pulled from the wrapped iterator,
+    /// whether by [`Tokens::next()`] or by looking ahead such as with [`Self::fill`], and capacity is kept for reuse, so this suits parses that rarely hold locations over a long stream.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abc".chars());
+    /// tokens.set_auto_commit(true);
+    /// assert_eq!(tokens.fill(2), 2);
+    /// tokens.next();
+    /// assert_eq!(tokens.buffer_len(), 1);
+    /// ```
+    pub fn set_auto_commit(&mut self, auto_commit: bool) {
+        self.buffer.auto_commit = auto_commit;
+    }
+
+    /// Drop consumed items and spare capacity if [`Self::set_auto_commit`] is on and nothing needs them.
+    fn auto_commit(&mut self) {
+        if self.buffer.auto_commit && self.checkout.borrow().is_empty() {
+            self.buffer.drain_to(self.cursor);
+            self.buffer
+                .elements
+                .shrink_to(self.buffer.len.max(AUTO_COMMIT_CAPACITY));
+        }
+    }
+
+    /// Cursor of the oldest item that the current location or a live location needs.
+    fn oldest_needed(&self) -> usize {
+        match self.checkout.borrow().first() {
+            Some(&x) => x.min(self.cursor),
+            None => self.cursor,
+        }
+    }
+
+    /// Drop the buffered items before [`Self::oldest_needed`].
+    fn drain_unneeded(&mut self) {
+        self.buffer.drain_to(self.oldest_needed());
+    }
+
+    /// Make room in the buffer for at least `additional` more items, such as before a long lookahead of known size.
+    pub fn reserve(&mut self, additional: usize) {
+        self.buffer.elements.reserve(additional);
+    }
+
+    /// Number of buffered items after the current location, which [`Tokens::next()`] will return before pulling from the wrapped iterator.
+    pub fn buffered_remaining(&self) -> usize {
+        self.buffer.oldest_elem_cursor + self.buffer.len - self.cursor
+    }
+
+    /// Remove the buffered tokens before the current location that no live location needs, returning them in order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("GET /".chars());
+    /// let start = tokens.location();
+    /// assert!(tokens.tokens("GET ".chars()));
+    /// drop(start);
+    /// assert_eq!(tokens.drain_consumed().collect::<String>(), "GET ");
+    /// assert_eq!(tokens.buffer_len(), 0);
+    /// ```
+    pub fn drain_consumed(&mut self) -> impl Iterator<Item = I::Item> {
+        let end = self.oldest_needed();
+        let consumed = (0..end.saturating_sub(self.buffer.oldest_elem_cursor))
+            .filter_map(|i| self.buffer.elements.get(i))
+            .collect::<Vec<_>>();
+        self.buffer.drain_to(end);
+        consumed.into_iter()
+    }
+
+    /// The token before the current location, if it is still buffered because a live location needs it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new(" x".chars());
+    /// let _start = tokens.location();
+    /// tokens.skip_while(|c| c.is_whitespace());
+    /// assert_eq!(tokens.last_consumed(), Some(' '));
+    /// ```
+    pub fn last_consumed(&self) -> Option<I::Item> {
+        let idx = self
+            .cursor
+            .checked_sub(self.buffer.oldest_elem_cursor)?
+            .checked_sub(1)?;
+        self.buffer.elements.get(idx)
+    }
+
+    /// The item at absolute stream `offset`, as given by [`TokenLocation::offset`], if it is buffered.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abcd".chars());
+    /// let _start = tokens.location();
+    /// tokens.take(3).consume();
+    /// assert_eq!(tokens.buffered_at(1), Some('b'));
+    /// assert_eq!(tokens.buffered_at(3), None);
+    /// ```
+    pub fn buffered_at(&self, offset: usize) -> Option<I::Item> {
+        let idx = offset.checked_sub(self.buffer.oldest_elem_cursor)?;
+        self.buffer.elements.get(idx)
+    }
+
+    /// Number of `\n` tokens between `from` and the current location, for char or byte streams.
+    ///
+    /// Returns [`None`] if `from` is after the current location or the tokens after it are no longer buffered.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new(b"a\nb\nc".iter().copied());
+    /// let start = tokens.location();
+    /// tokens.take(4).consume();
+    /// assert_eq!(tokens.count_newlines_since(&start), Some(2));
+    /// ```
+    pub fn count_newlines_since(&self, from: &StreamTokensLocation) -> Option<usize>
+    where
+        I::Item: PartialEq + From<u8>,
+    {
+        let start = from.cursor.checked_sub(self.buffer.oldest_elem_cursor)?;
+        let end = self.cursor - self.buffer.oldest_elem_cursor;
+        if start > end {
+            return None;
+        }
+        let newline = I::Item::from(b'\n');
+        Some(
+            (start..end)
+                .filter(|&i| self.buffer.elements.get(i).as_ref() == Some(&newline))
+                .count(),
+        )
+    }
+
+    /// Like [`Tokens::peek()`] but borrows the next token from the buffer instead of cloning it.
+    ///
+    /// The token is buffered even if no location needs it, and is returned from there by the next [`Tokens::next()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new([vec![1; 1024], vec![2]].into_iter());
+    /// assert_eq!(tokens.peek_ref().map(Vec::len), Some(1024));
+    /// assert_eq!(tokens.next().map(|v| v.len()), Some(1024));
+    /// ```
+    pub fn peek_ref(&mut self) -> Option<&I::Item>
+    where
+        Buf: core::ops::Index<usize, Output = I::Item>,
+    {
+        if self.cursor - self.buffer.oldest_elem_cursor >= self.buffer.len {
+            self.drain_unneeded();
+            let next = self.iter.next()?;
+            self.buffer.push(next);
+        }
+        Some(&self.buffer.elements[self.cursor - self.buffer.oldest_elem_cursor])
+    }
+
+    /// Classify the next token with `f` without consuming it, for example to choose which branch of a lexer to run.
+    /// Returns [`None`] at the end of the stream.
+    ///
+    /// Like [`Self::peek_ref`] the token stays buffered for the next [`Tokens::next()`], no location is created,
+    /// and consumed items nothing needs are dropped from the buffer.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("7x".chars());
+    /// assert_eq!(tokens.peek_classify(char::is_ascii_digit), Some(true));
+    /// assert_eq!(tokens.next(), Some('7'));
+    /// ```
+    pub fn peek_classify<C, F>(&mut self, f: F) -> Option<C>
+    where
+        F: FnOnce(&I::Item) -> C,
+    {
+        if self.fill(1) == 0 {
+            return None;
+        }
+        let item = self
+            .buffer
+            .elements
+            .get(self.cursor - self.buffer.oldest_elem_cursor)?;
+        Some(f(&item))
+    }
+
+    /// Pull items from the wrapped iterator until at least `n` are buffered after the current location, without advancing it.
+    /// Returns [`Self::buffered_remaining`], which is less than `n` if the iterator ended or a bounded buffer is full first.
+    ///
+    /// The items stay buffered until they are consumed even without a live location,
+    /// so a parser known to need `n` items can then run without waiting on a blocking source part way through.
+    /// Once consumed, they are dropped on the next pull from the wrapped iterator unless a live location needs them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abc".chars());
+    /// assert_eq!(tokens.fill(2), 2);
+    /// assert_eq!(tokens.fill(8), 3);
+    /// assert_eq!(tokens.collect::<String>(), "abc");
+    /// ```
+    pub fn fill(&mut self, n: usize) -> usize {
+        while self.buffered_remaining() < n && self.buffer_next() {}
+        self.buffered_remaining()
+    }
+
+    /// Whether [`Tokens::next()`] would return [`None`].
+    /// Any item pulled from the wrapped iterator to check is buffered for it to return.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("ab".chars());
+    /// let mut seen = String::new();
+    /// while !tokens.at_eof() {
+    ///     seen.extend(tokens.next());
+    /// }
+    /// assert_eq!(seen, "ab");
+    /// ```
+    pub fn at_eof(&mut self) -> bool {
+        self.fill(1) == 0
+    }
+
+    /// Pull one item into the back of the buffer, unless the iterator has ended
+    /// or a bounded buffer would have to drop items after the current location to fit it.
+    fn buffer_next(&mut self) -> bool {
+        if self
+            .buffer
+            .max_len
+            .is_some_and(|max| self.buffered_remaining() >= max)
+        {
+            return false;
+        }
+        self.drain_unneeded();
+        match self.iter.next() {
+            Some(item) => {
+                self.buffer.push(item);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move the cursor back by `n` tokens so they can be read again.
+    ///
+    /// Only tokens that are still buffered because some location needs them can be unread.
+    /// If fewer than `n` are buffered the cursor is unchanged and the most that could be unread is returned as the error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("ab".chars());
+    /// let start = tokens.location();
+    ///
+    /// assert_eq!(tokens.next(), Some('a'));
+    /// assert_eq!(tokens.rewind(1), Ok(()));
+    /// assert_eq!(tokens.next(), Some('a'));
+    /// assert_eq!(tokens.rewind(2), Err(1));
+    /// ```
+    pub fn rewind(&mut self, n: usize) -> Result<(), usize> {
+        let buffered = self.cursor - self.buffer.oldest_elem_cursor;
+        if n > buffered {
+            return Err(buffered);
+        }
+        self.cursor -= n;
+        Ok(())
+    }
+
+    /// Create a [`StreamTokensAnchor`] at the current location.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("key=value".chars());
+    /// let record_start = tokens.anchor();
+    ///
+    /// let loc = tokens.location();
+    /// tokens.take_while(|&c| c != '=').consume();
+    /// drop(loc);
+    /// assert_eq!(tokens.live_locations(), 0);
+    ///
+    /// tokens.set_location(record_start.location(&tokens).unwrap());
+    /// assert_eq!(tokens.collect::<String>(), "key=value");
+    /// ```
+    pub fn anchor(&self) -> StreamTokensAnchor {
+        self.anchors.set(self.anchors.get() + 1);
+        StreamTokensAnchor {
+            cursor: self.cursor,
+            location: Some(self.location()),
+            anchors: Rc::clone(&self.anchors),
+        }
+    }
+
+    /// Number of live [`StreamTokensLocation`]s, not counting [`StreamTokensAnchor`]s.
+    pub fn live_locations(&self) -> usize {
+        self.checkout.borrow().len() - self.anchors.get()
+    }
+
+    /// Like [`Tokens::location()`], but in debug builds the location is also recorded with `label`
+    /// for [`Self::oldest_live_label`] to report while it is live.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abc".chars());
+    /// tokens.next();
+    /// let _start = tokens.labeled_location("record_start");
+    /// tokens.next();
+    /// if let Some(oldest) = tokens.oldest_live_label() {
+    ///     assert_eq!(oldest, ("record_start", 1));
+    /// }
+    /// ```
+    pub fn labeled_location(&self, label: &'static str) -> StreamTokensLocation {
+        #[allow(unused_mut)]
+        let mut location = self.location();
+        #[cfg(debug_assertions)]
+        {
+            self.labels.borrow_mut().push((location.cursor, label));
+            location.label = Some((label, Rc::clone(&self.labels)));
+        }
+        #[cfg(not(debug_assertions))]
+        let _ = label;
+        location
+    }
+
+    /// Label and offset of the oldest live location if it came from [`Self::labeled_location`],
+    /// which is what keeps the buffer from dropping items after it.
+    ///
+    /// If an unlabeled location is as old, the label of one at the same offset is still returned.
+    /// Labels are only recorded in debug builds, so this is always [`None`] otherwise.
+    pub fn oldest_live_label(&self) -> Option<(&'static str, usize)> {
+        #[cfg(debug_assertions)]
+        {
+            let oldest = *self.checkout.borrow().first()?;
+            self.labels
+                .borrow()
+                .iter()
+                .find(|&&(cursor, _)| cursor == oldest)
+                .map(|&(cursor, label)| (label, cursor))
+        }
+        #[cfg(not(debug_assertions))]
+        None
+    }
+
+    /// Most [`StreamTokensLocation`]s and [`StreamTokensAnchor`]s that have been live at once,
+    /// counting those made by [`Tokens::location()`] and its wrappers but not clones of them.
+    /// Together with [`Self::peak_buffer_len`] this points out backtracking that holds on to too much of the stream.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abc".chars());
+    /// let outer = tokens.location();
+    /// {
+    ///     let _inner = tokens.location();
+    ///     tokens.next();
+    /// }
+    /// let _sibling = tokens.location();
+    /// drop(outer);
+    /// assert_eq!(tokens.max_location_depth(), 2);
+    /// ```
+    pub fn max_location_depth(&self) -> usize {
+        self.max_depth.get()
+    }
+
+    /// Number of live [`StreamTokensAnchor`]s that are not [acknowledged](StreamTokensAnchor::acknowledge).
+    pub fn live_anchors(&self) -> usize {
+        self.anchors.get()
+    }
+
+    /// Most items the buffer may hold, see [`StreamTokens::new_bounded`].
+    /// This is [`usize::MAX`] for an unbounded buffer.
+    pub fn buffer_capacity(&self) -> usize {
+        self.buffer.max_len.unwrap_or(usize::MAX)
+    }
+
+    /// Panic if the internal bookkeeping is inconsistent.
+    ///
+    /// Intended to be called by fuzz and property tests after each operation.
+    #[cfg(debug_assertions)]
+    pub fn check_invariants(&self) {
+        let checkout = self.checkout.borrow();
+        let buffer = &self.buffer;
+        assert!(
+            checkout.windows(2).all(|w| w[0] <= w[1]),
+            "checkout is sorted"
+        );
+        assert!(
+            self.anchors.get() <= checkout.len(),
+            "anchors are checked out"
+        );
+        assert!(buffer.len <= self.buffer_capacity(), "buffer within bound");
+        assert_eq!(
+            buffer.elements.len(),
+            buffer.len,
+            "buffer holds `len` items"
+        );
+        assert!(
+            buffer.oldest_elem_cursor <= self.cursor,
+            "cursor is buffered"
+        );
+        assert!(
+            self.cursor <= buffer.oldest_elem_cursor + buffer.len,
+            "items before cursor are buffered"
+        );
+        if buffer.max_len.is_none() {
+            if let Some(&oldest_needed) = checkout.first() {
+                assert!(
+                    buffer.oldest_elem_cursor <= oldest_needed,
+                    "items needed by locations are buffered"
+                );
+            }
+        }
+    }
+
+    /// Most items that have been buffered at once.
+    /// Useful for checking that a parser doesn't hold on to locations for longer than it needs to.
+    pub fn peak_buffer_len(&self) -> usize {
+        self.buffer.peak_len
+    }
+
+    /// Call `f` with the offset of the first item and the items whenever buffered items are dropped, usually because nothing needs them anymore,
+    /// for example to write them through to storage. Replaces any previous callback.
+    ///
+    /// Items that are never buffered, because no location needed them when they were read, aren't passed to `f`.
+    /// With a bounded buffer such as from [`Self::new_bounded`], items dropped to stay within the bound are passed to `f` too,
+    /// even if a live location still needs them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::{cell::RefCell, rc::Rc};
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let drained = Rc::new(RefCell::new(String::new()));
+    /// let mut tokens = StreamTokens::new("abc".chars());
+    /// let log = Rc::clone(&drained);
+    /// tokens.on_drain(move |_, items| log.borrow_mut().extend(items));
+    ///
+    /// let start = tokens.location();
+    /// tokens.take(2).consume();
+    /// drop(start);
+    /// tokens.next();
+    /// assert_eq!(*drained.borrow(), "ab");
+    /// ```
+    pub fn on_drain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &[I::Item]) + 'static,
+    {
+        self.buffer.on_drain = Some(DrainHook(Box::new(move |offset, elements: &Buf, n| {
+            let items = (0..n).filter_map(|i| elements.get(i)).collect::<Vec<_>>();
+            f(offset, &items);
+        })));
+    }
+}
+
+impl<I, Buffer> Tokens for StreamTokens<I, Buffer>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buffer: StreamTokensBuffer<I::Item>,
+{
+    type Item = I::Item;
+
+    type Location = StreamTokensLocation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Try buffer
+        {
+            // If buffer has needed element use buffer before getting new elements.
+            if let Some(val) = self
+                .buffer
+                .elements
+                .get(self.cursor - self.buffer.oldest_elem_cursor)
+            {
+                self.cursor += 1;
+                if let Some(rolling) = &mut self.rolling_hash {
+                    rolling.push(&val);
+                }
+                self.auto_commit();
+                return Some(val);
+            }
+        }
+
+        let checkout = self.checkout.borrow();
+        // Clear buffer of old values
+        {
+            // Remove old values no longer needed by any location
+            let min = match checkout.first() {
+                Some(&x) => x.min(self.cursor),
+                None => self.cursor,
+            };
+            self.buffer.drain_to(min);
+        }
+
+        // Handle cache miss
+        {
+            // Only advance the cursor if there is an item to advance past.
+            let next = self.iter.next()?;
+            self.cursor += 1;
+            if let Some(rolling) = &mut self.rolling_hash {
+                rolling.push(&next);
+            }
+            // Don't save to buffer if no locations exist which might need the value again
+            if checkout.is_empty() {
+                self.buffer.oldest_elem_cursor = self.cursor;
+                drop(checkout);
+                self.auto_commit();
+                Some(next)
+            } else {
+                self.buffer.push(next.clone());
+                Some(next)
+            }
+        }
+    }
+
+    fn location(&self) -> Self::Location {
+        self.checkout_at(self.cursor)
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        if let Err(location) = self.try_set_location(location) {
+            panic!(
+                "location at offset {} is no longer buffered",
+                location.cursor
+            );
+        }
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.cursor == location.cursor
+    }
+}
+
+impl<I, Buf> IntoTokens<I::Item> for StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone + core::fmt::Debug,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    type Tokens = Self;
+    fn into_tokens(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn stream_tokens_sanity_check() {
+        // In reality, one should always prefer to use StrTokens for strings:
+        let chars: &mut dyn Iterator<Item = char> = &mut "hello \n\t world".chars();
+        // Can't `chars.clone()` so:
+        let mut tokens = StreamTokens::new(chars);
+
+        let loc = tokens.location();
+        assert!(tokens.tokens("hello".chars()));
+
+        tokens.set_location(loc.clone());
+        assert!(tokens.tokens("hello".chars()));
+
+        tokens.skip_while(|c| c.is_whitespace());
+
+        assert!(tokens.tokens("world".chars()));
+
+        tokens.set_location(loc);
+        assert!(tokens.tokens("hello \n\t world".chars()));
+
+        assert_eq!(None, tokens.next());
+    }
 
     #[test]
-    fn stream_tokens_sanity_check() {
+    fn str_stream_tokens_sanity_check() {
         // In reality, one should always prefer to use StrTokens for strings:
         let chars: &mut dyn Iterator<Item = char> = &mut "hello \n\t world".chars();
         // Can't `chars.clone()` so:
-        let mut tokens = StreamTokens::new(chars);
+        let mut tokens = crate::StrStreamTokens::new(chars);
 
         let loc = tokens.location();
         assert!(tokens.tokens("hello".chars()));
@@ -271,11 +1381,9 @@ mod tests {
     }
 
     #[test]
-    fn str_stream_tokens_sanity_check() {
-        // In reality, one should always prefer to use StrTokens for strings:
+    fn vec_buffer_sanity_check() {
         let chars: &mut dyn Iterator<Item = char> = &mut "hello \n\t world".chars();
-        // Can't `chars.clone()` so:
-        let mut tokens = crate::StrStreamTokens::new(chars);
+        let mut tokens = StreamTokens::<_, Vec<char>>::new_with_buffer(chars);
 
         let loc = tokens.location();
         assert!(tokens.tokens("hello".chars()));
@@ -292,4 +1400,936 @@ mod tests {
 
         assert_eq!(None, tokens.next());
     }
+
+    #[test]
+    fn vec_buffer_drains() {
+        let mut tokens = StreamTokens::<_, Vec<char>>::new_with_buffer("abcdef".chars());
+
+        let first = tokens.location();
+        tokens.take(2).consume();
+        let second = tokens.location();
+        tokens.take(2).consume();
+        assert_eq!(tokens.buffer_len(), 4);
+
+        // Dropping the oldest location lets the next read drain up to the next oldest.
+        drop(first);
+        assert_eq!(tokens.next(), Some('e'));
+        assert_eq!(tokens.buffer_len(), 3);
+
+        tokens.set_location(second);
+        assert_eq!(tokens.collect::<String>(), "cdef");
+    }
+
+    #[test]
+    fn buffer_len_tracks_items() {
+        fn check<Buf: StreamTokensBuffer<char>>() {
+            let mut buf = Buf::default();
+            assert!(buf.is_empty());
+            buf.push_many(&['a', 'é', '✓', 'b']);
+            assert_eq!(buf.len(), 4);
+            buf.drain_front(2);
+            assert_eq!(buf.len(), 2);
+            assert_eq!(buf.get(0), Some('✓'));
+            buf.push('c');
+            assert_eq!(buf.len(), 3);
+            buf.drain_front(5);
+            assert!(buf.is_empty());
+        }
+        check::<VecDeque<char>>();
+        check::<Vec<char>>();
+        check::<String>();
+    }
+
+    #[test]
+    fn on_drain_reports_offsets() {
+        let drained = Rc::new(RefCell::new(Vec::new()));
+        let mut tokens = StreamTokens::new("abcdefg".chars());
+        let log = Rc::clone(&drained);
+        tokens.on_drain(move |offset, items| {
+            log.borrow_mut()
+                .push((offset, items.iter().collect::<String>()))
+        });
+
+        let first = tokens.location();
+        tokens.take(2).consume();
+        let second = tokens.location();
+        tokens.take(2).consume();
+        assert!(drained.borrow().is_empty());
+
+        drop(first);
+        tokens.next();
+        drop(second);
+        tokens.next();
+        // Unbuffered items aren't reported.
+        tokens.next();
+        assert_eq!(
+            *drained.borrow(),
+            [(0, String::from("ab")), (2, String::from("cde"))]
+        );
+    }
+
+    #[test]
+    fn on_drain_reports_bounded_eviction() {
+        let drained = Rc::new(RefCell::new(String::new()));
+        let mut tokens = StreamTokens::new_bounded("abcd".chars(), 2);
+        let log = Rc::clone(&drained);
+        tokens.on_drain(move |_, items| log.borrow_mut().extend(items));
+
+        let start = tokens.location();
+        tokens.take(4).consume();
+        // Dropped to stay within the bound although `start` still needs them.
+        assert_eq!(*drained.borrow(), "ab");
+        assert!(!start.is_valid(&tokens));
+    }
+
+    #[test]
+    fn reserve_avoids_reallocation() {
+        let mut tokens = StreamTokens::new("x".repeat(1000).into_bytes().into_iter());
+        let _start = tokens.location();
+        tokens.reserve(1000);
+        let capacity = tokens.buffer.elements.capacity();
+        assert!(capacity >= 1000);
+
+        assert_eq!(tokens.fill(1000), 1000);
+        assert_eq!(tokens.buffer.elements.capacity(), capacity);
+    }
+
+    #[test]
+    fn unfused_resumes_after_gap() {
+        fn gappy() -> impl Iterator<Item = u8> {
+            let mut calls = 0;
+            core::iter::from_fn(move || {
+                calls += 1;
+                (calls % 2 == 1).then_some(calls)
+            })
+        }
+
+        let mut tokens = StreamTokens::new(gappy());
+        assert_eq!(tokens.next(), Some(1));
+        assert_eq!(tokens.next(), None);
+        assert_eq!(tokens.next(), None);
+        assert!(tokens.at_eof());
+
+        let mut tokens = StreamTokens::new(gappy()).with_fused(false);
+        let start = tokens.location();
+        assert_eq!(tokens.next(), Some(1));
+        assert_eq!(tokens.next(), None);
+        assert_eq!(tokens.offset(), 1);
+        assert_eq!(tokens.next(), Some(3));
+        tokens.set_location(start);
+        assert_eq!(tokens.take(3).collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(tokens.next(), Some(5));
+    }
+
+    #[test]
+    fn buffer_push_many() {
+        let mut deque = VecDeque::from(['a']);
+        StreamTokensBuffer::push_many(&mut deque, &['b', 'c']);
+        assert_eq!(deque, ['a', 'b', 'c']);
+
+        let mut vec = alloc::vec!['a'];
+        StreamTokensBuffer::push_many(&mut vec, &['b', 'c']);
+        assert_eq!(vec, ['a', 'b', 'c']);
+
+        let mut string = String::from("a");
+        StreamTokensBuffer::push_many(&mut string, &['b', 'ç']);
+        assert_eq!(string, "abç");
+    }
+
+    /// Run random sequences of operations checking the result against a plain index into the input.
+    #[cfg(debug_assertions)]
+    fn random_ops(tokens: &mut StreamTokens<core::str::Chars<'_>, VecDeque<char>>, input: &str) {
+        let input = input.chars().collect::<Vec<_>>();
+        let mut reference = 0;
+        let mut locations = Vec::new();
+        // Deterministic xorshift so failures are reproducible.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        for _ in 0..10_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            match state % 4 {
+                0 | 1 => {
+                    assert_eq!(tokens.next(), input.as_slice().get(reference).copied());
+                    reference = (reference + 1).min(input.len());
+                }
+                2 if locations.len() < 8 => locations.push((tokens.location(), reference)),
+                _ if !locations.is_empty() => {
+                    let (loc, at) = locations.swap_remove(state as usize / 4 % locations.len());
+                    // Sometimes just drop the location instead.
+                    if state & 0x100 == 0 && tokens.try_set_location(loc).is_ok() {
+                        reference = at;
+                    }
+                }
+                _ => {}
+            }
+            tokens.check_invariants();
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn random_ops_keep_invariants() {
+        let input = "the quick brown fox jumps over the lazy dog".repeat(50);
+        random_ops(&mut StreamTokens::new(input.chars()), &input);
+        let mut bounded = StreamTokens::new_bounded(input.chars(), 5);
+        random_ops(&mut bounded, &input);
+        assert!(bounded.peak_buffer_len() <= 5);
+    }
+
+    #[test]
+    fn bounded_peek_respects_cap() {
+        let mut tokens = StreamTokens::new_bounded("abcdef".chars(), 2);
+        let start = tokens.location();
+        tokens.take(3).consume();
+
+        assert_eq!(tokens.peek(), Some('d'));
+        assert_eq!(tokens.peek(), Some('d'));
+        assert!(tokens.try_set_location(start).is_err());
+        assert_eq!(tokens.collect::<String>(), "def");
+        assert_eq!(tokens.peak_buffer_len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one item")]
+    fn bounded_zero_panics() {
+        StreamTokens::new_bounded("abc".chars(), 0);
+    }
+
+    #[test]
+    fn surrounded_by_keeps_buffer_bounded() {
+        let input = "[12] [345]\n".repeat(1000);
+        let mut tokens = StreamTokens::new(input.chars());
+
+        let mut sum = 0;
+        while let Some(n) = tokens.surrounded_by(
+            |t| {
+                t.optional(|t| {
+                    t.token('[').then_some(())?;
+                    let n = t.take_while(char::is_ascii_digit).parse::<u32, String>();
+                    t.token(']').then_some(n.ok()?)
+                })
+            },
+            |t| {
+                t.skip_while(char::is_ascii_whitespace);
+            },
+        ) {
+            sum += n;
+        }
+
+        assert_eq!(sum, 357 * 1000);
+        assert_eq!(tokens.next(), None);
+        // Only the item being parsed is ever buffered.
+        assert!(tokens.peak_buffer_len() <= "[345]".len());
+    }
+
+    #[test]
+    fn drain_consumed_header() {
+        let mut tokens = StreamTokens::new("HDR:body".chars());
+        let header = tokens.location();
+        assert!(tokens.tokens("HDR:".chars()));
+        let body = tokens.location();
+        assert!(tokens.tokens("bo".chars()));
+        assert_eq!(tokens.buffer_len(), 6);
+
+        // `header` still needs everything.
+        assert_eq!(tokens.drain_consumed().count(), 0);
+        drop(header);
+        assert_eq!(tokens.drain_consumed().collect::<String>(), "HDR:");
+        assert_eq!(tokens.buffer_len(), 2);
+
+        tokens.set_location(body);
+        assert_eq!(tokens.collect::<String>(), "body");
+    }
+
+    #[test]
+    fn drain_consumed_bounded_evicted() {
+        let mut tokens = StreamTokens::new_bounded("abcdef".chars(), 2);
+        let _start = tokens.location();
+        tokens.take(4).consume();
+        // The items `start` needs were already dropped to stay within the bound.
+        assert_eq!(tokens.drain_consumed().count(), 0);
+        assert_eq!(tokens.collect::<String>(), "ef");
+    }
+
+    #[test]
+    fn sep_by_all_keeps_buffer_bounded() {
+        let input = "1+22+333+".repeat(1000) + "4";
+        let mut tokens = StreamTokens::new(input.chars());
+
+        let (mut sum, mut seps) = (0, 0);
+        tokens
+            .sep_by_all(
+                |t| {
+                    t.take_while(char::is_ascii_digit)
+                        .parse::<u32, String>()
+                        .ok()
+                        .map(Some)
+                },
+                |t| t.token('+').then_some(None),
+            )
+            .as_iter()
+            .for_each(|item| match item {
+                Some(n) => sum += n,
+                None => seps += 1,
+            });
+
+        assert_eq!(sum, 356 * 1000 + 4);
+        assert_eq!(seps, 3 * 1000);
+        // A separator, the item after it, and the lookahead that ended the item.
+        assert!(tokens.peak_buffer_len() <= "+333+".len());
+    }
+
+    #[test]
+    fn last_consumed_is_buffered_item() {
+        let mut tokens = StreamTokens::new("ab".chars());
+        assert_eq!(tokens.last_consumed(), None);
+
+        // Not buffered without a location.
+        assert_eq!(tokens.next(), Some('a'));
+        assert_eq!(tokens.last_consumed(), None);
+
+        let loc = tokens.location();
+        assert_eq!(tokens.next(), Some('b'));
+        assert_eq!(tokens.last_consumed(), Some('b'));
+        assert_eq!(tokens.last_consumed(), Some('b'));
+        assert_eq!(tokens.offset(), 2);
+
+        tokens.set_location(loc);
+        assert_eq!(tokens.last_consumed(), None);
+    }
+
+    #[test]
+    fn at_eof_keeps_item() {
+        let mut tokens = StreamTokens::new("xy".chars());
+        assert!(!tokens.at_eof());
+        assert_eq!(tokens.next(), Some('x'));
+        assert!(!tokens.at_eof());
+        assert!(!tokens.at_eof());
+        assert_eq!(tokens.next(), Some('y'));
+        assert!(tokens.at_eof());
+        assert_eq!(tokens.next(), None);
+        assert_eq!(tokens.offset(), 2);
+
+        // Checking doesn't keep consumed items buffered.
+        let input = "x".repeat(100_000);
+        let mut tokens = StreamTokens::new(input.chars());
+        while !tokens.at_eof() {
+            tokens.next();
+        }
+        assert!(tokens.peak_buffer_len() <= 2);
+    }
+
+    #[test]
+    fn fill_buffers_ahead() {
+        let mut tokens = StreamTokens::new("0123456789".chars());
+        assert_eq!(tokens.next(), Some('0'));
+
+        assert_eq!(tokens.fill(8), 8);
+        assert_eq!(tokens.buffered_remaining(), 8);
+        assert_eq!(tokens.offset(), 1);
+
+        assert!(tokens.tokens("12345678".chars()));
+        assert_eq!(tokens.buffered_remaining(), 0);
+        assert_eq!(tokens.collect::<String>(), "9");
+
+        let mut tokens = StreamTokens::new_bounded("0123456789".chars(), 4);
+        assert_eq!(tokens.fill(8), 4);
+        assert_eq!(tokens.collect::<String>(), "0123456789");
+    }
+
+    #[test]
+    fn try_set_location_in_range() {
+        let mut tokens = StreamTokens::new_bounded("abcdef".chars(), 3);
+
+        tokens.next();
+        let loc = tokens.location();
+        assert!(tokens.tokens("bcd".chars()));
+
+        assert!(loc.is_valid(&tokens));
+        assert!(tokens.try_set_location(loc).is_ok());
+        assert!(tokens.tokens("bcdef".chars()));
+    }
+
+    #[test]
+    fn rewind_unread_one() {
+        let mut tokens = StreamTokens::new("abc".chars());
+        let _start = tokens.location();
+
+        assert!(tokens.tokens("ab".chars()));
+        assert_eq!(tokens.rewind(1), Ok(()));
+        assert_eq!(tokens.collect::<String>(), "bc");
+
+        // The end of the stream doesn't count as a token.
+        assert_eq!(tokens.rewind(1), Ok(()));
+        assert_eq!(tokens.next(), Some('c'));
+    }
+
+    #[test]
+    fn rewind_beyond_buffer() {
+        let mut tokens = StreamTokens::new("abcd".chars());
+        tokens.next();
+        let loc = tokens.location();
+        tokens.take(2).consume();
+
+        assert_eq!(tokens.rewind(3), Err(2));
+        assert_eq!(tokens.next(), Some('d'));
+
+        // Nothing is buffered without a location.
+        drop(loc);
+        assert_eq!(tokens.next(), None);
+        assert_eq!(tokens.rewind(1), Err(0));
+    }
+
+    #[test]
+    fn anchor_pins_while_locations_come_and_go() {
+        let mut tokens = StreamTokens::new("a1b2c3".chars());
+        let start = tokens.anchor();
+        assert_eq!(tokens.live_anchors(), 1);
+
+        loop {
+            let loc = tokens.location();
+            assert_eq!(tokens.live_locations(), 1);
+            if tokens.next().is_none() {
+                tokens.set_location(loc);
+                break;
+            }
+        }
+        assert_eq!(tokens.live_locations(), 0);
+        assert_eq!(tokens.live_anchors(), 1);
+        assert_eq!(tokens.buffer_len(), 6);
+
+        tokens.set_location(start.location(&tokens).unwrap());
+        assert!(tokens.tokens("a1b2c3".chars()));
+
+        let copy = start.clone();
+        assert_eq!(tokens.live_anchors(), 2);
+        drop(start);
+        drop(copy);
+        assert_eq!(tokens.live_anchors(), 0);
+        assert_eq!(tokens.live_locations(), 0);
+    }
+
+    #[test]
+    fn acknowledged_anchor_is_not_kept_buffered() {
+        let input = "x".repeat(10_000);
+        let mut tokens = StreamTokens::new(input.chars());
+        tokens.set_auto_commit(true);
+        let mut start = tokens.anchor();
+        let pinned = start.clone();
+        assert_eq!(tokens.live_anchors(), 2);
+
+        start.acknowledge();
+        start.acknowledge();
+        assert!(start.is_acknowledged() && start.clone().is_acknowledged());
+        assert_eq!(tokens.live_anchors(), 1);
+
+        // `pinned` still keeps everything buffered.
+        tokens.take(5000).consume();
+        assert_eq!(tokens.buffer_len(), 5000);
+        assert!(start.location(&tokens).is_some());
+
+        drop(pinned);
+        assert_eq!(tokens.live_anchors(), 0);
+        tokens.take(5000).consume();
+        assert!(tokens.peak_buffer_len() <= 5001);
+        assert!(tokens.buffer_len() <= 1);
+        assert_eq!(start.location(&tokens), None);
+        assert_eq!(tokens.live_locations(), 0);
+        assert_eq!(tokens.offset(), start.offset() + 10_000);
+        drop(start);
+        assert_eq!(tokens.live_locations(), 0);
+    }
+
+    #[test]
+    fn acknowledged_anchor_location_does_not_pin() {
+        let mut tokens = StreamTokens::new("abcdef".chars());
+        let mut start = tokens.anchor();
+        tokens.take(3).consume();
+        start.acknowledge();
+        assert_eq!(tokens.drain_consumed().collect::<String>(), "abc");
+
+        let loc = start.location(&tokens);
+        assert_eq!(loc, None);
+        assert_eq!(tokens.collect::<String>(), "def");
+        assert_eq!(tokens.buffer_len(), 0);
+    }
+
+    #[test]
+    fn try_set_location_out_of_range() {
+        let mut tokens = StreamTokens::new_bounded("abcdef".chars(), 3);
+
+        let loc = tokens.location();
+        assert!(tokens.tokens("abcd".chars()));
+
+        // "a" was dropped to stay within the bound.
+        assert!(!loc.is_valid(&tokens));
+        assert!(tokens.location().is_valid(&tokens));
+        let loc = tokens.try_set_location(loc).unwrap_err();
+        assert_eq!(loc.offset(), 0);
+        // Cursor is unchanged.
+        assert_eq!(tokens.collect::<String>(), "ef");
+    }
+
+    #[test]
+    fn set_location_to_current_cursor() {
+        let mut tokens = StreamTokens::new("ab".chars());
+        let start = tokens.location();
+        tokens.next();
+
+        for _ in 0..100 {
+            let loc = tokens.location();
+            tokens.set_location(loc);
+        }
+        assert_eq!(tokens.live_locations(), 1);
+        assert_eq!(*tokens.checkout.borrow(), [0]);
+
+        // Dropping out of order still removes the right entries.
+        let here = tokens.location();
+        let again = start.clone();
+        drop(start);
+        assert_eq!(*tokens.checkout.borrow(), [0, 1]);
+        drop(here);
+        drop(again);
+        assert_eq!(tokens.live_locations(), 0);
+        assert_eq!(tokens.collect::<String>(), "b");
+    }
+
+    #[test]
+    fn save_restore_loop_reuses_checkout_slots() {
+        let input = "x".repeat(1000);
+        let mut tokens = StreamTokens::new(input.chars());
+        let _outer = tokens.location();
+        let mut nested = [tokens.location(), tokens.location()];
+        let mut capacity = None;
+
+        // Save, advance and restore like a backtracking parser would.
+        for i in 0..1000 {
+            let loc = tokens.location();
+            tokens.next();
+            if i % 2 == 0 {
+                tokens.set_location(loc);
+                tokens.next();
+            }
+            nested[i % 2] = tokens.location();
+            // Only the first rounds of each kind may need to grow the checkout.
+            let now = tokens.checkout.borrow().capacity();
+            if i > 0 {
+                assert_eq!(*capacity.get_or_insert(now), now);
+            }
+        }
+        assert_eq!(tokens.live_locations(), 3);
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn clone_keeps_checkout_sorted() {
+        let mut tokens = StreamTokens::new("abc".chars());
+        let first = tokens.location();
+        tokens.next();
+        let newest = tokens.location();
+        let cloned = [newest.clone(), first.clone(), newest.clone()];
+        assert_eq!(*tokens.checkout.borrow(), [0, 0, 1, 1, 1]);
+        drop(cloned);
+        drop(newest);
+        assert_eq!(*tokens.checkout.borrow(), [0]);
+        drop(first);
+    }
+
+    #[test]
+    #[should_panic(expected = "no longer buffered")]
+    fn set_location_out_of_range_panics() {
+        let mut tokens = StreamTokens::new_bounded("abcdef".chars(), 1);
+
+        let loc = tokens.location();
+        tokens.take(2).consume();
+        tokens.set_location(loc);
+    }
+
+    /// Counts how often it is cloned.
+    #[derive(Debug)]
+    struct CloneCounter(Rc<Cell<usize>>);
+
+    impl Clone for CloneCounter {
+        fn clone(&self) -> Self {
+            self.0.set(self.0.get() + 1);
+            CloneCounter(Rc::clone(&self.0))
+        }
+    }
+
+    #[test]
+    fn peek_ref_does_not_clone() {
+        let clones = Rc::new(Cell::new(0));
+        let items = [0, 1].map(|_| CloneCounter(Rc::clone(&clones)));
+        let mut tokens = StreamTokens::new(items.into_iter());
+
+        assert!(tokens.peek_ref().is_some());
+        assert!(tokens.peek_ref().is_some());
+        assert_eq!(clones.get(), 0);
+        assert_eq!(tokens.buffer_len(), 1);
+        // The peeked item comes from the buffer.
+        assert!(tokens.next().is_some());
+        assert_eq!(clones.get(), 1);
+        assert!(tokens.peek_ref().is_some());
+        assert_eq!(tokens.buffer_len(), 1);
+    }
+
+    #[test]
+    fn peek_ref_keeps_locations() {
+        let mut tokens = StreamTokens::<_, Vec<char>>::new_with_buffer("abc".chars());
+        let start = tokens.location();
+        tokens.next();
+        assert_eq!(tokens.peek_ref(), Some(&'b'));
+        assert_eq!(tokens.next(), Some('b'));
+        assert_eq!(tokens.peek_ref(), Some(&'c'));
+        tokens.set_location(start);
+        assert_eq!(tokens.collect::<String>(), "abc");
+        assert_eq!(tokens.peek_ref(), None);
+    }
+
+    /// Sum of the digits in each `,` separated group, which rewinds over each separator.
+    fn parse_groups<I: Iterator<Item = char>>(
+        tokens: &mut StreamTokens<I, VecDeque<char>>,
+    ) -> Vec<u32> {
+        tokens
+            .sep_by(
+                |t| {
+                    t.take_while(char::is_ascii_digit)
+                        .as_iter()
+                        .filter_map(|c| c.to_digit(10))
+                        .reduce(|a, b| a + b)
+                },
+                |t| t.token(','),
+            )
+            .as_iter()
+            .collect()
+    }
+
+    #[test]
+    // `is_multiple_of` would need Rust 1.87.
+    #[allow(clippy::manual_is_multiple_of)]
+    fn recording_replays_identically() {
+        // A source that could give something different next time.
+        let mut n = 0u32;
+        let source = core::iter::from_fn(move || {
+            n += 7;
+            (n < 100).then(|| {
+                if n % 3 == 0 {
+                    ','
+                } else {
+                    char::from_digit(n % 10, 10).unwrap()
+                }
+            })
+        });
+        let mut tokens = StreamTokens::new(source);
+        tokens.start_recording();
+        let parsed = parse_groups(&mut tokens);
+        let recorded = tokens.stop_recording();
+        assert_eq!(recorded.len(), 14);
+
+        let mut replay = StreamTokens::from_vec(recorded);
+        assert_eq!(parse_groups(&mut replay), parsed);
+        assert_eq!(replay.next(), None);
+    }
+
+    #[test]
+    fn recording_window() {
+        let mut tokens = StreamTokens::new("abcdef".chars());
+        assert!(tokens.stop_recording().is_empty());
+        tokens.next();
+        tokens.start_recording();
+        let start = tokens.location();
+        assert_eq!(tokens.fill(2), 2);
+        tokens.set_location(start);
+        // Items pulled by `until` are only recorded once.
+        let mut tokens = tokens.until(|&c| c == 'e');
+        assert_eq!(tokens.collect::<String>(), "bcd");
+        assert_eq!(tokens.stop_recording(), ['b', 'c', 'd']);
+        tokens.next();
+        assert!(tokens.stop_recording().is_empty());
+    }
+
+    #[test]
+    fn auto_commit_million_tokens() {
+        let mut tokens = StreamTokens::new((0..1_000_000u32).map(|n| n % 10));
+        tokens.set_auto_commit(true);
+        let mut sum = 0u64;
+        while let Some(n) = tokens.next() {
+            if n == 9 {
+                // Occasional lookahead without holding a location.
+                tokens.fill(3);
+            }
+            sum += u64::from(n);
+        }
+        assert_eq!(sum, 4_500_000);
+        assert!(tokens.peak_buffer_len() <= 3);
+        assert_eq!(tokens.buffer_len(), 0);
+    }
+
+    #[test]
+    fn without_auto_commit_lookahead_drops_consumed() {
+        let mut tokens = StreamTokens::new((0..1_000_000u32).map(|n| n % 10));
+        while let Some(n) = tokens.next() {
+            if n == 9 {
+                tokens.fill(3);
+            }
+        }
+        assert!(tokens.peak_buffer_len() <= 4);
+    }
+
+    #[test]
+    fn auto_commit_frees_capacity() {
+        let mut tokens = StreamTokens::new(core::iter::repeat_n('x', 10_000));
+        tokens.set_auto_commit(true);
+        let start = tokens.location();
+        tokens.take(5_000).consume();
+        assert!(tokens.buffer.elements.capacity() >= 5_000);
+
+        // Still needed while the location is live.
+        tokens.next();
+        assert_eq!(tokens.buffer_len(), 5_001);
+
+        drop(start);
+        tokens.next();
+        assert_eq!(tokens.buffer_len(), 0);
+        assert!(tokens.buffer.elements.capacity() <= AUTO_COMMIT_CAPACITY);
+        assert_eq!(tokens.as_iter().count(), 4_998);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn oldest_live_label_reports_pin() {
+        let mut tokens = StreamTokens::new("0123456789".chars());
+        tokens.take(4).consume();
+        let record_start = tokens.labeled_location("record_start");
+        tokens.take(2).consume();
+        let field = tokens.labeled_location("field");
+        let copy = record_start.clone();
+        tokens.take(2).consume();
+        assert_eq!(tokens.oldest_live_label(), Some(("record_start", 4)));
+
+        drop(record_start);
+        assert_eq!(tokens.oldest_live_label(), Some(("record_start", 4)));
+        drop(copy);
+        assert_eq!(tokens.oldest_live_label(), Some(("field", 6)));
+
+        // An older unlabeled location is what pins the buffer now.
+        tokens.set_location(field);
+        let unlabeled = tokens.location();
+        tokens.next();
+        let _later = tokens.labeled_location("later");
+        assert_eq!(tokens.oldest_live_label(), None);
+        drop(unlabeled);
+        assert_eq!(tokens.oldest_live_label(), Some(("later", 7)));
+        assert!(tokens.labels.borrow().len() == 1);
+    }
+
+    #[test]
+    fn count_newlines_multi_line() {
+        let mut tokens = StreamTokens::new("one\ntwo\n\nthree\nfour".chars());
+        let start = tokens.location();
+        tokens.skip_while(|&c| c != 't');
+        assert_eq!(tokens.count_newlines_since(&start), Some(1));
+        let line_two = tokens.location();
+        tokens.skip_while(|&c| c != 'f');
+        assert_eq!(tokens.count_newlines_since(&start), Some(4));
+        assert_eq!(tokens.count_newlines_since(&line_two), Some(3));
+        assert_eq!(tokens.count_newlines_since(&tokens.location()), Some(0));
+
+        // A location ahead of the cursor.
+        let end = tokens.location();
+        tokens.set_location(line_two);
+        assert_eq!(tokens.count_newlines_since(&end), None);
+    }
+
+    #[test]
+    fn count_newlines_drained() {
+        let mut tokens = StreamTokens::new_bounded("a\nb\nc\n".chars(), 2);
+        let start = tokens.location();
+        tokens.take(4).consume();
+        assert_eq!(tokens.count_newlines_since(&start), None);
+    }
+
+    #[test]
+    fn with_prefix_then_iter() {
+        let mut tokens = StreamTokens::with_prefix(*b"abc", b"def".iter().copied());
+        assert_eq!(tokens.buffer_len(), 3);
+        assert_eq!(tokens.location().offset(), 0);
+        assert_eq!(tokens.next(), Some(b'a'));
+
+        let in_prefix = tokens.location();
+        assert!(tokens.tokens(*b"bcd"));
+        tokens.set_location(in_prefix);
+        assert_eq!(tokens.as_iter().collect::<Vec<_>>(), b"bcdef");
+
+        // Without locations the prefix is dropped once read like any other item.
+        let mut tokens = StreamTokens::with_prefix(['x'], "yz".chars());
+        tokens.take(2).consume();
+        assert_eq!(tokens.buffer_len(), 0);
+        assert_eq!(tokens.next(), Some('z'));
+    }
+
+    #[test]
+    fn buffered_at_absolute_offsets() {
+        let mut tokens = StreamTokens::new("0123456789".chars());
+        tokens.take(3).consume();
+        assert_eq!(tokens.buffered_at(2), None);
+
+        let backref = tokens.location();
+        tokens.take(4).consume();
+        assert_eq!(tokens.buffered_at(backref.offset() + 1), Some('4'));
+        assert_eq!(tokens.buffered_at(6), Some('6'));
+        assert_eq!(tokens.buffered_at(2), None);
+        assert_eq!(tokens.buffered_at(7), None);
+
+        // Items pulled ahead of the cursor are buffered too.
+        tokens.fill(2);
+        assert_eq!(tokens.buffered_at(8), Some('8'));
+        assert_eq!(tokens.buffered_at(9), None);
+    }
+
+    #[test]
+    fn fill_then_next_drops_consumed() {
+        let input = "x".repeat(100_000);
+        let mut tokens = StreamTokens::new(input.chars());
+        while tokens.fill(1) > 0 {
+            tokens.next();
+        }
+        assert!(tokens.peak_buffer_len() <= 2);
+        assert_eq!(tokens.buffer_len(), 0);
+    }
+
+    #[test]
+    fn fill_then_parse_without_pulls() {
+        let pulls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&pulls);
+        let source = "123,456,789,0"
+            .chars()
+            .inspect(move |_| counter.set(counter.get() + 1));
+        let mut tokens = StreamTokens::new(source);
+
+        assert_eq!(tokens.fill(10), 10);
+        assert_eq!(pulls.get(), 10);
+        let numbers = tokens
+            .sep_by(
+                |t| {
+                    t.take_while(char::is_ascii_digit)
+                        .parse::<u32, String>()
+                        .ok()
+                },
+                |t| t.token(','),
+            )
+            .as_iter()
+            .take(2)
+            .collect::<Vec<_>>();
+        assert_eq!(numbers, [123, 456]);
+        tokens.token(',');
+        assert_eq!(tokens.take(2).parse::<u32, String>(), Ok(78));
+        assert_eq!(pulls.get(), 10);
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum SourceError {
+        WouldBlock,
+    }
+
+    #[test]
+    fn unfused_resumes_after_transient_error() {
+        let mut results = alloc::vec![Ok('b'), Err(SourceError::WouldBlock), Ok('a')];
+        let error = Rc::new(Cell::new(None));
+        let latched = Rc::clone(&error);
+        let source = core::iter::from_fn(move || {
+            // Don't pull past an error until it has been handled.
+            if latched.get().is_some() {
+                return None;
+            }
+            match results.pop()? {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    latched.set(Some(e));
+                    None
+                }
+            }
+        });
+        let mut tokens = StreamTokens::new(source).with_fused(false);
+
+        assert_eq!(tokens.next(), Some('a'));
+        assert_eq!(tokens.next(), None);
+        assert_eq!(tokens.next(), None);
+        assert_eq!(error.take(), Some(SourceError::WouldBlock));
+        // Cleared, so pulling resumes.
+        assert_eq!(tokens.next(), Some('b'));
+        assert_eq!(tokens.next(), None);
+        assert_eq!(error.get(), None);
+    }
+
+    /// `x` or `(` group `)`, trying the group first.
+    fn parse_group_depth(t: &mut impl Tokens<Item = char>) -> Option<usize> {
+        let group = t.optional(|t| {
+            if t.next()? != '(' {
+                return None;
+            }
+            let depth = parse_group_depth(t)?;
+            (t.next()? == ')').then_some(depth + 1)
+        });
+        group.or_else(|| t.optional(|t| (t.next()? == 'x').then_some(0)))
+    }
+
+    #[test]
+    fn max_location_depth_tracks_nesting() {
+        for depth in 0..5 {
+            let input = "(".repeat(depth) + "x" + &")".repeat(depth);
+            let mut tokens = StreamTokens::new(input.chars());
+            assert_eq!(parse_group_depth(&mut tokens), Some(depth));
+            // One location per open group, plus the failed attempt at another group inside the last.
+            assert_eq!(tokens.max_location_depth(), depth + 1);
+            assert_eq!(tokens.live_locations(), 0);
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum CharKind {
+        Digit,
+        Alpha,
+        Symbol,
+    }
+
+    fn classify(c: &char) -> CharKind {
+        if c.is_ascii_digit() {
+            CharKind::Digit
+        } else if c.is_alphabetic() {
+            CharKind::Alpha
+        } else {
+            CharKind::Symbol
+        }
+    }
+
+    #[test]
+    fn peek_classify_does_not_advance() {
+        let mut tokens = StreamTokens::new("a1+".chars());
+        for (kind, c) in [
+            (CharKind::Alpha, 'a'),
+            (CharKind::Digit, '1'),
+            (CharKind::Symbol, '+'),
+        ] {
+            let before = tokens.location().offset();
+            assert_eq!(tokens.peek_classify(classify), Some(kind));
+            assert_eq!(
+                tokens.peek_classify(classify),
+                tokens.peek().as_ref().map(classify)
+            );
+            assert_eq!(tokens.location().offset(), before);
+            assert_eq!(tokens.live_locations(), 0);
+            assert_eq!(tokens.next(), Some(c));
+        }
+        assert_eq!(tokens.peek_classify(classify), None);
+
+        // A lexer loop doesn't keep consumed items buffered.
+        let input = "a1+".repeat(30_000);
+        let mut tokens = StreamTokens::new(input.chars());
+        while tokens.peek_classify(classify).is_some() {
+            tokens.next();
+        }
+        assert!(tokens.peak_buffer_len() <= 2);
+    }
 }
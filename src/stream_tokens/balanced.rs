@@ -0,0 +1,118 @@
+use super::{captured_tokens::CapturedTokens, StreamTokens, StreamTokensBuffer};
+use alloc::vec::Vec;
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StreamTokens::take_balanced`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BalanceError {
+    /// The next token wasn't the opening delimiter.
+    NotOpen,
+    /// The stream ended before the matching closing delimiter.
+    Unclosed,
+}
+
+impl fmt::Display for BalanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BalanceError::NotOpen => write!(f, "expected opening delimiter"),
+            BalanceError::Unclosed => write!(f, "stream ended before closing delimiter"),
+        }
+    }
+}
+
+impl core::error::Error for BalanceError {}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone + PartialEq,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Consume an `open` token through its matching `close`, counting nested pairs,
+    /// and return the tokens between them as [`CapturedTokens`].
+    /// On error the location is reset to where matching began.
+    ///
+    /// If `open` and `close` are the same token there is no nesting, and the next one closes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("(a(b)c)d".chars());
+    /// let mut inner = tokens.take_balanced('(', ')').unwrap();
+    /// assert_eq!(inner.collect::<String>(), "a(b)c");
+    /// assert_eq!(tokens.next(), Some('d'));
+    /// ```
+    pub fn take_balanced(
+        &mut self,
+        open: I::Item,
+        close: I::Item,
+    ) -> Result<CapturedTokens<I::Item>, BalanceError> {
+        let from = self.location();
+        if !self.token(open.clone()) {
+            return Err(BalanceError::NotOpen);
+        }
+        let mut items = Vec::new();
+        let mut depth = 0usize;
+        loop {
+            let Some(item) = self.next() else {
+                self.set_location(from);
+                return Err(BalanceError::Unclosed);
+            };
+            if item == close {
+                if depth == 0 {
+                    return Ok(CapturedTokens::new(items));
+                }
+                depth -= 1;
+            } else if item == open {
+                depth += 1;
+            }
+            items.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn take_balanced_nested() {
+        let mut tokens = StreamTokens::new("((x)(y(z)))(w)".chars());
+        let mut inner = tokens.take_balanced('(', ')').unwrap();
+        assert_eq!(inner.collect::<String>(), "(x)(y(z))");
+        let mut inner = tokens.take_balanced('(', ')').unwrap();
+        assert_eq!(inner.collect::<String>(), "w");
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn take_balanced_immediately_closed() {
+        let mut tokens = StreamTokens::new("[]x".chars());
+        let mut inner = tokens.take_balanced('[', ']').unwrap();
+        assert_eq!(inner.next(), None);
+        assert_eq!(tokens.next(), Some('x'));
+    }
+
+    #[test]
+    fn take_balanced_unbalanced() {
+        let mut tokens = StreamTokens::new("(a(b)".chars());
+        assert_eq!(tokens.take_balanced('(', ')'), Err(BalanceError::Unclosed));
+        assert_eq!(tokens.collect::<String>(), "(a(b)");
+
+        let mut tokens = StreamTokens::new("a(b)".chars());
+        assert_eq!(tokens.take_balanced('(', ')'), Err(BalanceError::NotOpen));
+        assert_eq!(tokens.next(), Some('a'));
+    }
+
+    #[test]
+    fn take_balanced_same_delimiter() {
+        let mut tokens = StreamTokens::new("|a|b|".chars());
+        let mut inner = tokens.take_balanced('|', '|').unwrap();
+        assert_eq!(inner.collect::<String>(), "a");
+        assert_eq!(tokens.next(), Some('b'));
+    }
+}
@@ -0,0 +1,224 @@
+use super::{StreamTokens, StreamTokensBuffer, StreamTokensLocation};
+use core::{fmt, str::FromStr};
+use yap::Tokens;
+
+/// Error from [`StreamTokens::parse_utf8`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseUtf8Error<E> {
+    /// The bytes weren't valid UTF-8.
+    Utf8(core::str::Utf8Error),
+    /// The [`FromStr`] impl failed.
+    Parse(E),
+}
+
+impl<E: fmt::Display> fmt::Display for ParseUtf8Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseUtf8Error::Utf8(e) => e.fmt(f),
+            ParseUtf8Error::Parse(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for ParseUtf8Error<E> {}
+
+/// Byte-order mark found by [`StreamTokens::consume_bom`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bom {
+    /// `EF BB BF`
+    Utf8,
+    /// `FF FE`
+    Utf16Le,
+    /// `FE FF`
+    Utf16Be,
+}
+
+impl Bom {
+    /// The bytes of the mark.
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Bom::Utf8 => &[0xef, 0xbb, 0xbf],
+            Bom::Utf16Le => &[0xff, 0xfe],
+            Bom::Utf16Be => &[0xfe, 0xff],
+        }
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator<Item = u8>,
+    Buf: StreamTokensBuffer<u8>,
+{
+    /// Consume a byte-order mark if the stream continues with one, and return which it was.
+    /// Otherwise the location is left unchanged, even if part of a mark matched.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Bom, StreamTokens, Tokens};
+    ///
+    /// let mut tokens = StreamTokens::new([0xef, 0xbb, 0xbf, b'a'].into_iter());
+    /// assert_eq!(tokens.consume_bom(), Some(Bom::Utf8));
+    /// assert_eq!(tokens.consume_bom(), None);
+    /// assert_eq!(tokens.next(), Some(b'a'));
+    /// ```
+    pub fn consume_bom(&mut self) -> Option<Bom> {
+        [Bom::Utf8, Bom::Utf16Le, Bom::Utf16Be]
+            .into_iter()
+            .find(|bom| self.tokens(bom.as_bytes().iter().copied()))
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator<Item = u8>,
+    Buf: StreamTokensBuffer<u8> + core::ops::Deref<Target = [u8]>,
+{
+    /// Like [`Tokens::parse()`] for a stream of UTF-8 bytes, but parses the buffered bytes in place instead of collecting them first.
+    ///
+    /// Requires a buffer that derefs to a slice, such as [`Vec<u8>`](alloc::vec::Vec).
+    /// On error the location is reset.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::StreamTokens;
+    ///
+    /// let mut tokens = StreamTokens::<_, Vec<u8>>::new_with_buffer(b"1234".iter().copied());
+    /// assert_eq!(tokens.parse_utf8::<u16>(), Ok(1234));
+    /// ```
+    pub fn parse_utf8<Out>(&mut self) -> Result<Out, ParseUtf8Error<Out::Err>>
+    where
+        Out: FromStr,
+    {
+        // Fill rest of buffer with the wrapped stream before parsing everything.
+        let from = self.location();
+        while self.next().is_some() {}
+        // Parse everything.
+        let res = core::str::from_utf8(
+            &self.buffer.elements[from.cursor - self.buffer.oldest_elem_cursor..],
+        )
+        .map_err(ParseUtf8Error::Utf8)
+        .and_then(|s| s.parse().map_err(ParseUtf8Error::Parse));
+        // Reset location on error.
+        if res.is_err() {
+            self.set_location(from);
+        }
+        res
+    }
+
+    /// Borrow the buffered bytes between two locations without copying them.
+    ///
+    /// Returns `None` if `to` is before `from` or if any of the span is no longer (or not yet) buffered.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{StreamTokens, Tokens};
+    ///
+    /// let mut tokens =
+    ///     StreamTokens::<_, Vec<u8>>::new_with_buffer([0x78, 0x56, 0x34, 0x12, 0xff].into_iter());
+    /// let from = tokens.location();
+    /// tokens.take(4).consume();
+    /// let to = tokens.location();
+    ///
+    /// let bytes = tokens.buffered_slice_between(&from, &to).unwrap();
+    /// assert_eq!(u32::from_le_bytes(bytes.try_into().unwrap()), 0x12345678);
+    /// ```
+    pub fn buffered_slice_between(
+        &self,
+        from: &StreamTokensLocation,
+        to: &StreamTokensLocation,
+    ) -> Option<&[u8]> {
+        let start = from.cursor.checked_sub(self.buffer.oldest_elem_cursor)?;
+        let end = to.cursor.checked_sub(self.buffer.oldest_elem_cursor)?;
+        <[u8]>::get(&self.buffer.elements, start..end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn parse_utf8_in_place() {
+        let mut tokens = StreamTokens::<_, Vec<u8>>::new_with_buffer(b"65535".iter().copied());
+        assert_eq!(tokens.parse_utf8::<u16>(), Ok(65535));
+        assert_eq!(tokens.next(), None);
+
+        // Parse errors reset the location.
+        let mut tokens = StreamTokens::<_, Vec<u8>>::new_with_buffer(b"65536".iter().copied());
+        assert!(matches!(
+            tokens.parse_utf8::<u16>(),
+            Err(ParseUtf8Error::Parse(_))
+        ));
+        assert_eq!(tokens.collect::<Vec<_>>(), b"65536");
+
+        // As do invalid bytes.
+        let mut tokens = StreamTokens::<_, Vec<u8>>::new_with_buffer([b'1', 0xff].into_iter());
+        assert!(matches!(
+            tokens.parse_utf8::<u16>(),
+            Err(ParseUtf8Error::Utf8(_))
+        ));
+        assert_eq!(tokens.collect::<Vec<_>>(), [b'1', 0xff]);
+    }
+
+    #[test]
+    fn parse_utf8_after_prefix() {
+        let mut tokens = StreamTokens::<_, Vec<u8>>::new_with_buffer(b"n=-12".iter().copied());
+        assert!(tokens.tokens(b"n=".iter().copied()));
+        assert_eq!(tokens.parse_utf8::<i8>(), Ok(-12));
+    }
+
+    #[test]
+    fn buffered_slice_between_reads_u32() {
+        let mut tokens = StreamTokens::<_, Vec<u8>>::new_with_buffer(
+            [0xaa, 0x01, 0x00, 0x00, 0x80, 0xbb].into_iter(),
+        );
+        assert_eq!(tokens.next(), Some(0xaa));
+
+        let from = tokens.location();
+        tokens.take(4).consume();
+        let to = tokens.location();
+
+        let bytes: [u8; 4] = tokens
+            .buffered_slice_between(&from, &to)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(u32::from_le_bytes(bytes), 0x8000_0001);
+        assert_eq!(tokens.buffered_slice_between(&to, &from), None);
+        assert_eq!(tokens.next(), Some(0xbb));
+    }
+
+    #[test]
+    fn consume_bom_present() {
+        for bom in [Bom::Utf8, Bom::Utf16Le, Bom::Utf16Be] {
+            let mut tokens = StreamTokens::new(bom.as_bytes().iter().copied().chain([b'x']));
+            assert_eq!(tokens.consume_bom(), Some(bom));
+            assert_eq!(tokens.collect::<Vec<_>>(), b"x");
+        }
+    }
+
+    #[test]
+    fn consume_bom_absent() {
+        let mut tokens = StreamTokens::new(b"abc".iter().copied());
+        assert_eq!(tokens.consume_bom(), None);
+        assert_eq!(tokens.collect::<Vec<_>>(), b"abc");
+
+        // A mark is only recognised as a whole.
+        let mut tokens = StreamTokens::new([0xef, 0xbb, b'x'].into_iter());
+        assert_eq!(tokens.consume_bom(), None);
+        assert_eq!(tokens.collect::<Vec<_>>(), [0xef, 0xbb, b'x']);
+    }
+
+    #[test]
+    fn consume_bom_partial_at_eof() {
+        for partial in [&[0xef, 0xbb][..], &[0xff], &[]] {
+            let mut tokens = StreamTokens::new(partial.iter().copied());
+            assert_eq!(tokens.consume_bom(), None);
+            assert_eq!(tokens.collect::<Vec<_>>(), partial);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use alloc::vec::Vec;
+use yap::{TokenLocation, Tokens};
+
+/// [`Tokens`] over items captured from a [`StreamTokens`](crate::StreamTokens) by [`StreamTokens::capture`](crate::StreamTokens::capture).
+///
+/// The items are owned so they can be replayed independently of the stream they came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedTokens<Item> {
+    items: Vec<Item>,
+    cursor: usize,
+}
+
+/// This implements [`TokenLocation`] for [`CapturedTokens`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapturedTokensLocation(usize);
+
+impl TokenLocation for CapturedTokensLocation {
+    fn offset(&self) -> usize {
+        self.0
+    }
+}
+
+impl<Item> CapturedTokens<Item> {
+    pub(crate) fn new(items: Vec<Item>) -> Self {
+        Self { items, cursor: 0 }
+    }
+}
+
+impl<Item: Clone> Tokens for CapturedTokens<Item> {
+    type Item = Item;
+
+    type Location = CapturedTokensLocation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.items.get(self.cursor)?.clone();
+        self.cursor += 1;
+        Some(item)
+    }
+
+    fn location(&self) -> Self::Location {
+        CapturedTokensLocation(self.cursor)
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        self.cursor = location.0;
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.cursor == location.0
+    }
+}
@@ -0,0 +1,82 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StreamTokens::checked`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumError {
+    /// The verifier rejected what was parsed.
+    Mismatch,
+}
+
+impl fmt::Display for ChecksumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChecksumError::Mismatch => write!(f, "checksum mismatch"),
+        }
+    }
+}
+
+impl core::error::Error for ChecksumError {}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Run `body`, then `verify` on its output, which may consume more tokens such as a trailing checksum.
+    /// If `verify` returns `false` the location is reset to before `body`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{ChecksumError, StreamTokens, Tokens};
+    ///
+    /// let mut tokens = StreamTokens::new([1, 2, 3, 6].into_iter());
+    /// let sum = tokens.checked(
+    ///     |t| t.take(3).as_iter().fold(0u8, u8::wrapping_add),
+    ///     |&sum, t| t.next() == Some(sum),
+    /// );
+    /// assert_eq!(sum, Ok(6));
+    /// ```
+    pub fn checked<T, F, V>(&mut self, body: F, verify: V) -> Result<T, ChecksumError>
+    where
+        F: FnOnce(&mut Self) -> T,
+        V: FnOnce(&T, &mut Self) -> bool,
+    {
+        let from = self.location();
+        let out = body(self);
+        if verify(&out, self) {
+            Ok(out)
+        } else {
+            self.set_location(from);
+            Err(ChecksumError::Mismatch)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn xor_packet<I: Iterator<Item = u8>>(
+        tokens: &mut StreamTokens<I, alloc::collections::VecDeque<u8>>,
+    ) -> Result<Vec<u8>, ChecksumError> {
+        tokens.checked(
+            |t| t.take(3).collect::<Vec<_>>(),
+            |body, t| t.next() == Some(body.iter().fold(0, |acc, b| acc ^ b)),
+        )
+    }
+
+    #[test]
+    fn checked_valid_and_invalid() {
+        let mut tokens = StreamTokens::new([1, 2, 4, 7, 1, 1, 1, 0].into_iter());
+        assert_eq!(xor_packet(&mut tokens), Ok(alloc::vec![1, 2, 4]));
+
+        // Checksum should be 1.
+        assert_eq!(xor_packet(&mut tokens), Err(ChecksumError::Mismatch));
+        assert_eq!(tokens.collect::<Vec<_>>(), [1, 1, 1, 0]);
+    }
+}
@@ -0,0 +1,132 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use core::fmt;
+use yap::Tokens;
+
+/// [`Tokens`] merging adjacent items of a [`StreamTokens`], returned by [`StreamTokens::coalesce`].
+///
+/// Locations are those of the wrapped tokens at the start of a merged item.
+pub struct CoalesceTokens<T, F> {
+    tokens: T,
+    f: F,
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for CoalesceTokens<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoalesceTokens")
+            .field("tokens", &self.tokens)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, F> CoalesceTokens<T, F> {
+    /// The wrapped tokens.
+    pub fn into_inner(self) -> T {
+        self.tokens
+    }
+}
+
+impl<T, F> Tokens for CoalesceTokens<T, F>
+where
+    T: Tokens,
+    F: FnMut(&T::Item, &T::Item) -> Option<T::Item>,
+{
+    type Item = T::Item;
+
+    type Location = T::Location;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut item = self.tokens.next()?;
+        loop {
+            let boundary = self.tokens.location();
+            let merged = self.tokens.next().and_then(|next| (self.f)(&item, &next));
+            match merged {
+                Some(merged) => item = merged,
+                None => {
+                    self.tokens.set_location(boundary);
+                    return Some(item);
+                }
+            }
+        }
+    }
+
+    fn location(&self) -> Self::Location {
+        self.tokens.location()
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        self.tokens.set_location(location);
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.tokens.is_at_location(location)
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Merge runs of adjacent items, like [`Iterator`] adapters that coalesce.
+    /// Each item is merged with the next one for as long as `f` returns [`Some`] merged item.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{StreamTokens, Tokens};
+    ///
+    /// let mut tokens = StreamTokens::new("a   b c".chars())
+    ///     .coalesce(|&a, &b| (a == ' ' && b == ' ').then_some(' '));
+    /// assert_eq!(tokens.collect::<String>(), "a b c");
+    /// ```
+    pub fn coalesce<F>(self, f: F) -> CoalesceTokens<Self, F>
+    where
+        F: FnMut(&I::Item, &I::Item) -> Option<I::Item>,
+    {
+        CoalesceTokens { tokens: self, f }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::String, vec, vec::Vec};
+    use yap::TokenLocation;
+
+    fn spaces(a: &char, b: &char) -> Option<char> {
+        (*a == ' ' && *b == ' ').then_some(' ')
+    }
+
+    #[test]
+    fn coalesce_spaces() {
+        let mut tokens = StreamTokens::new("  x  y ".chars()).coalesce(spaces);
+        assert_eq!(tokens.next(), Some(' '));
+        assert_eq!(tokens.location().offset(), 2);
+        assert_eq!(tokens.collect::<String>(), "x y ");
+    }
+
+    #[test]
+    fn coalesce_rewinds_across_merge() {
+        let mut tokens = StreamTokens::new("a   b".chars()).coalesce(spaces);
+        assert_eq!(tokens.next(), Some('a'));
+        let before_run = tokens.location();
+        assert_eq!(tokens.next(), Some(' '));
+        let after_run = tokens.location();
+        assert_eq!(after_run.offset(), 4);
+        assert_eq!(tokens.next(), Some('b'));
+
+        tokens.set_location(before_run);
+        assert_eq!(tokens.next(), Some(' '));
+        assert!(tokens.is_at_location(&after_run));
+        assert_eq!(tokens.collect::<String>(), "b");
+    }
+
+    #[test]
+    fn coalesce_sums_runs() {
+        let tokens = StreamTokens::new(vec![1, 2, 0, 3, 4, 5].into_iter());
+        // Merge while the total stays below 4.
+        let mut tokens = tokens.coalesce(|&a, &b| (a + b < 4).then_some(a + b));
+        assert_eq!(tokens.as_iter().collect::<Vec<_>>(), [3, 3, 4, 5]);
+    }
+}
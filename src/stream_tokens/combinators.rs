@@ -0,0 +1,1379 @@
+use super::{
+    captured_tokens::CapturedTokens, rolling_hash::RollingHash, Buffer, Source, StreamTokens,
+    StreamTokensBuffer, StreamTokensLocation,
+};
+use alloc::vec::Vec;
+use yap::Tokens;
+
+/// See [`StreamTokens::take_while_map`].
+struct TakeWhileMap<'a, I: Iterator, Buf, F> {
+    tokens: &'a mut StreamTokens<I, Buf>,
+    f: F,
+    done: bool,
+}
+
+impl<I, Buf, T, F> Tokens for TakeWhileMap<'_, I, Buf, F>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+    F: FnMut(I::Item) -> Option<T>,
+{
+    type Item = T;
+
+    type Location = StreamTokensLocation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let loc = self.tokens.location();
+        let mapped = self.tokens.next().and_then(&mut self.f);
+        if mapped.is_none() {
+            // Leave the token that didn't map for whatever parses next.
+            self.tokens.set_location(loc);
+            self.done = true;
+        }
+        mapped
+    }
+
+    fn location(&self) -> Self::Location {
+        self.tokens.location()
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        self.done = false;
+        self.tokens.set_location(location);
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.tokens.is_at_location(location)
+    }
+}
+
+/// See [`StreamTokens::take_while_indexed`].
+struct TakeWhileIndexed<'a, I: Iterator, Buf, F> {
+    tokens: &'a mut StreamTokens<I, Buf>,
+    start: usize,
+    pred: F,
+    done: bool,
+}
+
+impl<I, Buf, F> Tokens for TakeWhileIndexed<'_, I, Buf, F>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+    F: FnMut(usize, &I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    type Location = StreamTokensLocation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let loc = self.tokens.location();
+        // Derived from the cursor so rewinding within the run gives the same index again.
+        let idx = self.tokens.cursor - self.start;
+        match self.tokens.next() {
+            Some(item) if (self.pred)(idx, &item) => Some(item),
+            _ => {
+                self.tokens.set_location(loc);
+                self.done = true;
+                None
+            }
+        }
+    }
+
+    fn location(&self) -> Self::Location {
+        self.tokens.location()
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        self.done = false;
+        self.tokens.set_location(location);
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.tokens.is_at_location(location)
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Parse `item`s separated by `sep`, folding each one into an accumulator with `combine`.
+    ///
+    /// Like [`Tokens::sep_by`] followed by [`Iterator::fold`], but no location is held across items
+    /// so the buffer stays bounded by the size of a single item no matter how many are parsed.
+    /// If `sep` matches but the following `item` doesn't, the location is reset to before `sep`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("1,2,3;".chars());
+    /// let sum = tokens.separated_fold(
+    ///     0,
+    ///     |t| t.next().and_then(|c| c.to_digit(10)),
+    ///     |t| t.token(','),
+    ///     |acc, n| acc + n,
+    /// );
+    /// assert_eq!(sum, 6);
+    /// assert_eq!(tokens.next(), Some(';'));
+    /// ```
+    pub fn separated_fold<B, T, F, G, H>(
+        &mut self,
+        init: B,
+        mut item: F,
+        mut sep: G,
+        mut combine: H,
+    ) -> B
+    where
+        F: FnMut(&mut Self) -> Option<T>,
+        G: FnMut(&mut Self) -> bool,
+        H: FnMut(B, T) -> B,
+    {
+        let Some(first) = self.optional(&mut item) else {
+            return init;
+        };
+        let mut acc = combine(init, first);
+        loop {
+            // Only lives for one iteration so consumed items can be dropped from the buffer.
+            let loc = self.location();
+            match sep(self).then(|| item(self)).flatten() {
+                Some(x) => acc = combine(acc, x),
+                None => {
+                    self.set_location(loc);
+                    return acc;
+                }
+            }
+        }
+    }
+
+    /// Run `f` until it returns [`None`], collecting the results.
+    ///
+    /// The location is reset to before the attempt that failed.
+    /// No location is held across attempts so the buffer only needs to hold one attempt at a time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("ababa".chars());
+    /// let abs = tokens.many0(|t| t.tokens("ab".chars()).then_some(()));
+    /// assert_eq!(abs.len(), 2);
+    /// assert_eq!(tokens.next(), Some('a'));
+    /// ```
+    pub fn many0<T, F>(&mut self, mut f: F) -> Vec<T>
+    where
+        F: FnMut(&mut Self) -> Option<T>,
+    {
+        let mut out = Vec::new();
+        while let Some(x) = self.optional(&mut f) {
+            out.push(x);
+        }
+        out
+    }
+
+    /// Like [`Self::many0`] but returns [`None`] if `f` doesn't succeed at least once.
+    pub fn many1<T, F>(&mut self, f: F) -> Option<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> Option<T>,
+    {
+        let out = self.many0(f);
+        (!out.is_empty()).then_some(out)
+    }
+
+    /// Consume tokens for as long as `f` maps them to [`Some`], yielding the mapped values.
+    /// The first token `f` maps to [`None`] is not consumed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("12a".chars());
+    /// let digits: Vec<u32> = tokens.take_while_map(|c| c.to_digit(10)).collect();
+    ///
+    /// assert_eq!(digits, [1, 2]);
+    /// assert_eq!(tokens.next(), Some('a'));
+    /// ```
+    pub fn take_while_map<'a, T, F>(&'a mut self, f: F) -> impl Tokens<Item = T> + 'a
+    where
+        F: FnMut(I::Item) -> Option<T> + 'a,
+    {
+        TakeWhileMap {
+            tokens: self,
+            f,
+            done: false,
+        }
+    }
+
+    /// Consume tokens up to, but not including, the first one matching `pred`.
+    /// Returns whether such a token was found before the stream ended.
+    ///
+    /// Skipped tokens are only buffered if an older location needs them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("garbage;next".chars());
+    /// assert!(tokens.skip_until(|&c| c == ';'));
+    /// assert_eq!(tokens.collect::<String>(), ";next");
+    /// ```
+    pub fn skip_until<F>(&mut self, mut pred: F) -> bool
+    where
+        F: FnMut(&I::Item) -> bool,
+    {
+        loop {
+            let loc = self.location();
+            match self.next() {
+                None => return false,
+                Some(item) if pred(&item) => {
+                    self.set_location(loc);
+                    return true;
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Recover from a parse error by discarding tokens up to the next one matching `is_sync`,
+    /// such as the `;` ending a statement, so the next production can start from there.
+    /// Returns how many tokens were discarded.
+    ///
+    /// See [`Self::skip_until`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("x = = 1; y = 2;".chars());
+    /// assert_eq!(tokens.recover_to(|&c| c == ';'), 7);
+    /// assert!(tokens.tokens("; y".chars()));
+    /// ```
+    pub fn recover_to<F>(&mut self, is_sync: F) -> usize
+    where
+        F: FnMut(&I::Item) -> bool,
+    {
+        let start = self.cursor;
+        self.skip_until(is_sync);
+        self.cursor - start
+    }
+
+    /// Peek at the next two tokens without consuming them.
+    /// Either is [`None`] if the stream ends before it.
+    pub fn peek_pair(&mut self) -> (Option<I::Item>, Option<I::Item>) {
+        let loc = self.location();
+        let pair = (self.next(), self.next());
+        self.set_location(loc);
+        pair
+    }
+
+    /// Parse items with `item` until `terminator` matches, consuming the terminator.
+    ///
+    /// `terminator` is tried before each item and doesn't consume anything unless it returns `true`.
+    /// The first error from `item`, which includes the stream ending before the terminator if `item` rejects that, is returned
+    /// with the location reset to where parsing began.
+    /// If `item` succeeds without consuming anything, such as at the end of the stream, parsing stops after that item
+    /// without the terminator, since it would otherwise never end.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("a b END".chars());
+    /// let items = tokens.parse_until(
+    ///     |t| {
+    ///         let item = t.next().filter(char::is_ascii_lowercase).ok_or("expected item");
+    ///         t.token(' ');
+    ///         item
+    ///     },
+    ///     |t| t.tokens("END".chars()),
+    /// );
+    /// assert_eq!(items, Ok(vec!['a', 'b']));
+    /// ```
+    pub fn parse_until<T, E, F, G>(&mut self, mut item: F, mut terminator: G) -> Result<Vec<T>, E>
+    where
+        F: FnMut(&mut Self) -> Result<T, E>,
+        G: FnMut(&mut Self) -> bool,
+    {
+        let from = self.location();
+        let mut items = Vec::new();
+        while self.optional(|t| terminator(t).then_some(())).is_none() {
+            let before = self.cursor;
+            match item(self) {
+                Ok(out) => {
+                    items.push(out);
+                    if self.cursor == before {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    self.set_location(from);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    /// Run `f`, returning what it parsed or `default` if it returns [`None`], in which case the location is reset.
+    ///
+    /// Unlike [`Tokens::optional()`] followed by [`Option::unwrap_or`], consumed items that no other location needs
+    /// are dropped from the buffer before returning instead of on the next pull.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("-5".chars());
+    /// let negative = tokens.opt_or(|t| t.token('-').then_some(true), false);
+    /// assert!(negative);
+    /// assert_eq!(tokens.buffer_len(), 0);
+    /// ```
+    pub fn opt_or<T, F>(&mut self, f: F, default: T) -> T
+    where
+        F: FnOnce(&mut Self) -> Option<T>,
+    {
+        let out = self.optional(f).unwrap_or(default);
+        self.drain_unneeded();
+        out
+    }
+
+    /// Run `f`, resetting the location if it returns [`Err`] so that the error can be propagated with the stream untouched.
+    ///
+    /// Like [`Tokens::optional_err()`], but as with [`Self::opt_or`] consumed items that no other location needs
+    /// are dropped from the buffer before returning.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// fn two_digits(t: &mut impl Tokens<Item = char>) -> Result<u32, &'static str> {
+    ///     let a = t.next().and_then(|c| c.to_digit(10)).ok_or("digit")?;
+    ///     let b = t.next().and_then(|c| c.to_digit(10)).ok_or("digit")?;
+    ///     Ok(a * 10 + b)
+    /// }
+    ///
+    /// let mut tokens = StreamTokens::new("12x".chars());
+    /// assert_eq!(tokens.try_rewinding(|t| two_digits(t)), Ok(12));
+    /// assert_eq!(tokens.try_rewinding(|t| two_digits(t)), Err("digit"));
+    /// assert_eq!(tokens.next(), Some('x'));
+    /// ```
+    pub fn try_rewinding<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Self) -> Result<T, E>,
+    {
+        let out = self.optional_err(f);
+        self.drain_unneeded();
+        out
+    }
+
+    /// Parse `prefix` then `body`, returning what `body` parsed.
+    /// If either fails the location is reset to before `prefix`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("-1".chars());
+    /// let n = tokens.preceded(|t| t.token('-'), |t| t.next()?.to_digit(10));
+    /// assert_eq!(n, Some(1));
+    /// ```
+    pub fn preceded<T, P, F>(&mut self, prefix: P, body: F) -> Option<T>
+    where
+        P: FnOnce(&mut Self) -> bool,
+        F: FnOnce(&mut Self) -> Option<T>,
+    {
+        self.optional(|t| prefix(t).then(|| body(t)).flatten())
+    }
+
+    /// Parse `body` then `suffix`, returning what `body` parsed.
+    /// If either fails the location is reset to before `body`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("1;".chars());
+    /// let n = tokens.terminated(|t| t.next()?.to_digit(10), |t| t.token(';'));
+    /// assert_eq!(n, Some(1));
+    /// ```
+    pub fn terminated<T, F, S>(&mut self, body: F, suffix: S) -> Option<T>
+    where
+        F: FnOnce(&mut Self) -> Option<T>,
+        S: FnOnce(&mut Self) -> bool,
+    {
+        self.optional(|t| body(t).filter(|_| suffix(t)))
+    }
+
+    /// Parse `open`, `body` then `close`, returning what `body` parsed.
+    /// If any of them fail the location is reset to before `open`.
+    ///
+    /// Only the one location is held while parsing, so nothing is buffered after this returns unless an older location needs it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("(1)".chars());
+    /// let n = tokens.delimited(|t| t.token('('), |t| t.next()?.to_digit(10), |t| t.token(')'));
+    /// assert_eq!(n, Some(1));
+    /// ```
+    pub fn delimited<T, O, F, C>(&mut self, open: O, body: F, close: C) -> Option<T>
+    where
+        O: FnOnce(&mut Self) -> bool,
+        F: FnOnce(&mut Self) -> Option<T>,
+        C: FnOnce(&mut Self) -> bool,
+    {
+        self.optional(|t| {
+            if !open(t) {
+                return None;
+            }
+            body(t).filter(|_| close(t))
+        })
+    }
+
+    /// Try each of `parsers` in order from the same location, returning the first [`Some`].
+    /// The location is reset after each parser that returns [`None`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("false".chars());
+    /// let parsed = tokens.alt(&mut [
+    ///     &mut |t| t.tokens("true".chars()).then_some(true),
+    ///     &mut |t| t.tokens("false".chars()).then_some(false),
+    /// ]);
+    /// assert_eq!(parsed, Some(false));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn alt<T>(&mut self, parsers: &mut [&mut dyn FnMut(&mut Self) -> Option<T>]) -> Option<T> {
+        parsers
+            .iter_mut()
+            .find_map(|parser| self.optional(|t| parser(t)))
+    }
+
+    /// Consume and return the next token if it matches `pred`.
+    /// Otherwise nothing is consumed and [`None`] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("-1".chars());
+    /// assert_eq!(tokens.consume_if(|&c| c == '+'), None);
+    /// assert_eq!(tokens.consume_if(|&c| c == '-'), Some('-'));
+    /// assert_eq!(tokens.next(), Some('1'));
+    /// ```
+    pub fn consume_if<F>(&mut self, mut pred: F) -> Option<I::Item>
+    where
+        F: FnMut(&I::Item) -> bool,
+    {
+        let loc = self.location();
+        match self.next() {
+            Some(item) if pred(&item) => Some(item),
+            _ => {
+                self.set_location(loc);
+                None
+            }
+        }
+    }
+
+    /// Consume and return the next token if it is one of `set`, like [`Self::consume_if`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("+-".chars());
+    /// assert_eq!(tokens.consume_one_of(['+', '-']), Some('+'));
+    /// assert_eq!(tokens.consume_one_of(['*', '/']), None);
+    /// ```
+    pub fn consume_one_of<const N: usize>(&mut self, set: [I::Item; N]) -> Option<I::Item>
+    where
+        I::Item: PartialEq,
+    {
+        self.consume_if(|item| set.contains(item))
+    }
+
+    /// Like [`Tokens::take_while`] but `pred` is also given the zero-based index of the token within the run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("ffff1".chars());
+    /// let hex = tokens
+    ///     .take_while_indexed(|idx, c| idx < 4 && c.is_ascii_hexdigit())
+    ///     .collect::<String>();
+    /// assert_eq!(hex, "ffff");
+    /// assert_eq!(tokens.next(), Some('1'));
+    /// ```
+    pub fn take_while_indexed<'a, F>(&'a mut self, pred: F) -> impl Tokens<Item = I::Item> + 'a
+    where
+        F: FnMut(usize, &I::Item) -> bool + 'a,
+    {
+        TakeWhileIndexed {
+            start: self.cursor,
+            tokens: self,
+            pred,
+            done: false,
+        }
+    }
+
+    /// Run `f`, also returning the tokens it consumed as [`CapturedTokens`] that can be parsed again independently.
+    ///
+    /// With a bounded buffer, such as from [`StreamTokens::new_bounded`], only the consumed tokens still buffered are captured.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abc".chars());
+    /// let ((), mut captured) = tokens.capture(|t| t.take(2).consume());
+    ///
+    /// assert_eq!(captured.collect::<String>(), "ab");
+    /// assert_eq!(tokens.next(), Some('c'));
+    /// ```
+    pub fn capture<T, F>(&mut self, f: F) -> (T, CapturedTokens<I::Item>)
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let from = self.location();
+        let out = f(self);
+        // `from` kept everything `f` consumed buffered unless the buffer is bounded.
+        let items = (from.cursor.max(self.buffer.oldest_elem_cursor)..self.cursor)
+            .filter_map(|i| self.buffer.elements.get(i - self.buffer.oldest_elem_cursor))
+            .collect::<Vec<_>>();
+        (out, CapturedTokens::new(items))
+    }
+
+    /// Consume the rest of the stream, yielding each token with the location just before it.
+    ///
+    /// Each location keeps its token and everything after it buffered while it is live,
+    /// so holding on to all of them buffers the whole stream. Drop or convert them, such as with
+    /// [`TokenLocation::offset`](yap::TokenLocation::offset), if only positions are needed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{TokenLocation, Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("ab".chars());
+    /// let spans = tokens
+    ///     .enumerate_locations()
+    ///     .map(|(loc, c)| (loc.offset(), c))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(spans, [(0, 'a'), (1, 'b')]);
+    /// ```
+    pub fn enumerate_locations(
+        &mut self,
+    ) -> impl Iterator<Item = (StreamTokensLocation, I::Item)> + '_ {
+        core::iter::from_fn(move || {
+            let location = self.location();
+            let item = self.next()?;
+            Some((location, item))
+        })
+    }
+
+    /// Like [`Tokens::take_while()`] but stops after `max` tokens, so an adversarially long run can't be consumed.
+    /// Also returns whether `max` tokens matched, in which case the run may be longer.
+    ///
+    /// The matching tokens are found and buffered up front, so the returned tokens don't call `pred` again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("12345".chars());
+    /// let (mut digits, capped) = tokens.take_while_max(3, char::is_ascii_digit);
+    /// assert_eq!(digits.collect::<String>(), "123");
+    /// assert!(capped);
+    /// ```
+    pub fn take_while_max<F>(
+        &mut self,
+        max: usize,
+        mut pred: F,
+    ) -> (impl Tokens<Item = I::Item> + '_, bool)
+    where
+        F: FnMut(&I::Item) -> bool,
+    {
+        let from = self.location();
+        let mut len = 0;
+        while len < max && self.consume_if(&mut pred).is_some() {
+            len += 1;
+        }
+        self.set_location(from);
+        (self.take(len), len == max)
+    }
+
+    /// Split the rest of the stream into records ending at tokens that `is_delim` matches, each [`Self::capture`]d as its own [`CapturedTokens`].
+    ///
+    /// Getting a record consumes it and its delimiter, which is not part of the record.
+    /// Only the current record is buffered, since no location is kept once it has been captured.
+    /// Like [`str::split_terminator`], there is no empty record after a delimiter at the end of the stream.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("ab,,c".chars());
+    /// let records = tokens
+    ///     .split_on(|&c| c == ',')
+    ///     .map(|mut record| record.collect::<String>())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(records, ["ab", "", "c"]);
+    /// ```
+    pub fn split_on<'a, F>(
+        &'a mut self,
+        mut is_delim: F,
+    ) -> impl Iterator<Item = CapturedTokens<I::Item>> + 'a
+    where
+        F: FnMut(&I::Item) -> bool + 'a,
+    {
+        let mut done = false;
+        core::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let (len, record) = self.capture(|t| t.skip_while(|item| !is_delim(item)));
+            // Consume the delimiter.
+            if self.next().is_none() {
+                done = true;
+                if len == 0 {
+                    return None;
+                }
+            }
+            Some(record)
+        })
+    }
+
+    /// End the stream before the first token after the current location that `stop` matches.
+    /// Tokens after it are never pulled from the wrapped iterator, and any already buffered are dropped.
+    ///
+    /// Locations from before calling this remain valid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let tokens = StreamTokens::new("frame;rest".chars());
+    /// let mut frame = tokens.until(|&c| c == ';');
+    /// assert_eq!(frame.collect::<String>(), "frame");
+    /// ```
+    pub fn until<F>(mut self, mut stop: F) -> StreamTokens<impl Iterator<Item = I::Item>, Buf>
+    where
+        F: FnMut(&I::Item) -> bool,
+    {
+        // Rebuild the buffer without the items after the cursor so they can be checked against `stop`.
+        let consumed = self.cursor - self.buffer.oldest_elem_cursor;
+        let mut buffer = Buffer {
+            oldest_elem_cursor: self.buffer.oldest_elem_cursor,
+            elements: Buf::default(),
+            len: 0,
+            max_len: self.buffer.max_len,
+            peak_len: self.buffer.peak_len,
+            auto_commit: self.buffer.auto_commit,
+            on_drain: None,
+        };
+        let mut ahead = Vec::with_capacity(self.buffer.len - consumed);
+        buffer.on_drain = self.buffer.on_drain.take();
+        for i in 0..self.buffer.len {
+            let item = self
+                .buffer
+                .elements
+                .get(i)
+                .expect("buffer holds `len` items");
+            if i < consumed {
+                buffer.push(item);
+            } else {
+                ahead.push(item);
+            }
+        }
+        let fused = self.iter.fused;
+        let recorded = self.iter.recorded.take();
+        let unrecorded = ahead.len() + self.iter.unrecorded;
+        StreamTokens {
+            iter: Source {
+                fused,
+                exhausted: false,
+                recorded,
+                unrecorded,
+                iter: ahead
+                    .into_iter()
+                    .chain(self.iter)
+                    .take_while(move |item| !stop(item)),
+            },
+            cursor: self.cursor,
+            buffer,
+            checkout: self.checkout,
+            anchors: self.anchors,
+            max_depth: self.max_depth,
+            rolling_hash: self.rolling_hash.map(RollingHash::with_source),
+            #[cfg(debug_assertions)]
+            labels: self.labels,
+        }
+    }
+
+    /// Run `f`, also returning a view of the tokens it consumed.
+    /// The location is back after them once the view is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("foo_1(".chars());
+    /// let (len, mut ident) = tokens.recognize(|t| t.skip_while(|c| c.is_alphanumeric() || *c == '_'));
+    /// assert_eq!(len, 5);
+    /// assert_eq!(ident.collect::<String>(), "foo_1");
+    /// drop(ident);
+    /// assert_eq!(tokens.next(), Some('('));
+    /// ```
+    pub fn recognize<T, F>(&mut self, f: F) -> (T, impl Tokens<Item = I::Item> + '_)
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let from = self.location();
+        let out = f(self);
+        let to = self.location();
+        (out, self.slice(from, to))
+    }
+
+    /// Run `f`, also returning the locations before and after it.
+    ///
+    /// Both locations keep the tokens between them buffered until dropped, so the span can be rewound to or sliced later.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, TokenLocation, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("x=42".chars());
+    /// tokens.take(2).consume();
+    /// let (n, from, to) = tokens.spanned(|t| t.take_while(char::is_ascii_digit).parse::<u8, String>());
+    /// assert_eq!(n, Ok(42));
+    /// assert_eq!((from.offset(), to.offset()), (2, 4));
+    /// ```
+    pub fn spanned<T, F>(&mut self, f: F) -> (T, StreamTokensLocation, StreamTokensLocation)
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let from = self.location();
+        let out = f(self);
+        (out, from, self.location())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{collections::VecDeque, string::String, vec};
+    use yap::TokenLocation;
+
+    #[test]
+    fn separated_fold_bounded() {
+        let input = core::iter::repeat_n("12,", 10_000)
+            .flat_map(str::chars)
+            .chain("12!".chars());
+        let mut tokens = StreamTokens::new(input);
+
+        let sum = tokens.separated_fold(
+            0u32,
+            |t| {
+                t.take_while(char::is_ascii_digit)
+                    .parse::<u32, String>()
+                    .ok()
+            },
+            |t| t.token(','),
+            |acc, n| acc + n,
+        );
+
+        assert_eq!(sum, 12 * 10_001);
+        assert_eq!(tokens.collect::<String>(), "!");
+        assert!(tokens.peak_buffer_len() < 8);
+    }
+
+    #[test]
+    fn separated_fold_rewinds_trailing_separator() {
+        let mut tokens = StreamTokens::new("1,2,x".chars());
+
+        let count = tokens.separated_fold(
+            0,
+            |t| t.next().filter(char::is_ascii_digit),
+            |t| t.token(','),
+            |acc, _| acc + 1,
+        );
+
+        assert_eq!(count, 2);
+        assert_eq!(tokens.collect::<String>(), ",x");
+    }
+
+    #[test]
+    fn capture_and_replay() {
+        let mut tokens = StreamTokens::new("123+4".chars());
+
+        let (n, mut captured) = tokens.capture(|t| {
+            t.take_while(char::is_ascii_digit)
+                .parse::<u32, String>()
+                .ok()
+        });
+        assert_eq!(n, Some(123));
+        assert_eq!(tokens.next(), Some('+'));
+
+        // Re-parse the same chars as individual digits.
+        let digits = captured
+            .as_iter()
+            .filter_map(|c| c.to_digit(10))
+            .collect::<Vec<_>>();
+        assert_eq!(digits, [1, 2, 3]);
+    }
+
+    #[test]
+    fn enumerate_locations_offsets() {
+        let mut tokens = StreamTokens::new("xyz".chars());
+        tokens.next();
+        let pairs = tokens.enumerate_locations().collect::<Vec<_>>();
+        assert_eq!(tokens.live_locations(), 2);
+        for (i, (location, item)) in pairs.iter().enumerate() {
+            assert_eq!(location.offset(), i + 1);
+            assert_eq!(Some(*item), "xyz".chars().nth(i + 1));
+        }
+
+        // The locations can still be rewound to.
+        let (location, _) = pairs.into_iter().next().unwrap();
+        tokens.set_location(location);
+        assert_eq!(tokens.collect::<String>(), "yz");
+        assert_eq!(tokens.live_locations(), 0);
+    }
+
+    #[test]
+    fn capture_bounded_keeps_newest() {
+        let mut tokens = StreamTokens::new_bounded("abcdefgh".chars(), 2);
+        let ((), mut captured) = tokens.capture(|t| t.take(5).consume());
+        assert_eq!(captured.collect::<String>(), "de");
+        assert_eq!(tokens.collect::<String>(), "fgh");
+
+        let mut tokens = StreamTokens::new_bounded("abcdef\nxy".chars(), 2);
+        let records = tokens
+            .split_on(|&c| c == '\n')
+            .map(|mut record| record.collect::<String>())
+            .collect::<Vec<_>>();
+        // Looking at the delimiter takes one of the two slots.
+        assert_eq!(records, ["f", "xy"]);
+    }
+
+    #[test]
+    fn split_on_newline_records() {
+        let input = (0..1000).map(|n| alloc::format!("{n} {}\n", n * 2));
+        let mut tokens = StreamTokens::new(input.flat_map(|line| line.chars().collect::<Vec<_>>()));
+
+        let mut count = 0;
+        for mut record in tokens.split_on(|&c| c == '\n') {
+            let a = record
+                .take_while(char::is_ascii_digit)
+                .parse::<u32, String>()
+                .unwrap();
+            assert!(record.token(' '));
+            let b = record
+                .take_while(char::is_ascii_digit)
+                .parse::<u32, String>();
+            assert_eq!(b, Ok(a * 2));
+            assert_eq!(record.next(), None);
+            count += 1;
+        }
+        assert_eq!(count, 1000);
+        assert!(tokens.peak_buffer_len() < 12);
+        assert_eq!(tokens.live_locations(), 0);
+    }
+
+    #[test]
+    fn split_on_edges() {
+        let split = |input: &str| {
+            let mut tokens = StreamTokens::new(input.chars());
+            tokens
+                .split_on(|&c| c == ';')
+                .map(|mut record| record.collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        assert!(split("").is_empty());
+        assert_eq!(split(";"), [""]);
+        assert_eq!(split("a;"), ["a"]);
+        assert_eq!(split(";a;;b"), ["", "a", "", "b"]);
+    }
+
+    fn digit_pair<I: Iterator<Item = char>>(
+        t: &mut StreamTokens<I, alloc::collections::VecDeque<char>>,
+    ) -> Option<u32> {
+        let tens = t.next()?.to_digit(10)?;
+        Some(tens * 10 + t.next()?.to_digit(10)?)
+    }
+
+    #[test]
+    fn opt_or_releases_buffer() {
+        let mut tokens = StreamTokens::new("12ab".chars());
+        assert_eq!(tokens.opt_or(digit_pair, 0), 12);
+        assert_eq!(tokens.buffer_len(), 0);
+
+        // The default leaves the failed attempt's items buffered for what comes next.
+        assert_eq!(tokens.opt_or(digit_pair, 0), 0);
+        assert_eq!(tokens.buffer_len(), 1);
+        assert_eq!(tokens.next(), Some('a'));
+
+        // Items an outer location needs are kept.
+        let outer = tokens.location();
+        assert_eq!(tokens.opt_or(|t| t.next(), 'x'), 'b');
+        assert_eq!(tokens.buffer_len(), 1);
+        drop(outer);
+        assert_eq!(tokens.opt_or(|t| t.next(), 'x'), 'x');
+        assert_eq!(tokens.buffer_len(), 0);
+    }
+
+    #[test]
+    fn preceded_and_terminated() {
+        let mut tokens = StreamTokens::new("#12#3".chars());
+        assert_eq!(tokens.preceded(|t| t.token('#'), digit_pair), Some(12));
+        // Prefix fails.
+        assert_eq!(tokens.preceded(|t| t.token('!'), digit_pair), None);
+        // Body fails after the prefix matched.
+        assert_eq!(tokens.preceded(|t| t.token('#'), digit_pair), None);
+        assert_eq!(tokens.collect::<String>(), "#3");
+
+        let mut tokens = StreamTokens::new("12;34,".chars());
+        assert_eq!(tokens.terminated(digit_pair, |t| t.token(';')), Some(12));
+        // Suffix fails after the body matched.
+        assert_eq!(tokens.terminated(digit_pair, |t| t.token(';')), None);
+        assert_eq!(tokens.terminated(|t| t.next(), |t| t.token(',')), None);
+        assert_eq!(tokens.collect::<String>(), "34,");
+    }
+
+    #[test]
+    fn delimited_rewinds_fully() {
+        let delimited = |input: &str| {
+            let mut tokens = StreamTokens::new(input.chars());
+            let out = tokens.delimited(|t| t.token('['), digit_pair, |t| t.token(']'));
+            (out, tokens.collect::<String>())
+        };
+        assert_eq!(delimited("[42]x"), (Some(42), "x".into()));
+        assert_eq!(delimited("(42]"), (None, "(42]".into()));
+        assert_eq!(delimited("[4]"), (None, "[4]".into()));
+        assert_eq!(delimited("[42)"), (None, "[42)".into()));
+
+        // Nothing stays buffered once it is done.
+        let input = core::iter::repeat_n("[12]".chars(), 1000).flatten();
+        let mut tokens = StreamTokens::new(input);
+        while tokens
+            .delimited(|t| t.token('['), digit_pair, |t| t.token(']'))
+            .is_some()
+        {}
+        assert!(tokens.peak_buffer_len() <= 4);
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn many_zero_matches() {
+        let mut tokens = StreamTokens::new("1x".chars());
+        assert!(tokens.many0(digit_pair).is_empty());
+        assert_eq!(tokens.many1(digit_pair), None);
+        // Failed attempts are rewound.
+        assert_eq!(tokens.collect::<String>(), "1x");
+    }
+
+    #[test]
+    fn many_several_matches() {
+        let mut tokens = StreamTokens::new("1234567".chars());
+        assert_eq!(tokens.many1(digit_pair), Some(vec![12, 34, 56]));
+        assert_eq!(tokens.collect::<String>(), "7");
+    }
+
+    #[test]
+    fn many_bounded() {
+        let mut tokens = StreamTokens::new(core::iter::repeat_n('4', 10_001));
+        assert_eq!(tokens.many0(digit_pair).len(), 5_000);
+        assert_eq!(tokens.next(), Some('4'));
+        assert!(tokens.peak_buffer_len() <= 2);
+    }
+
+    #[test]
+    fn take_while_map_leaves_unmapped_token() {
+        let mut tokens = StreamTokens::new("1234abc".chars());
+
+        let mut digits = tokens.take_while_map(|c| c.to_digit(10));
+        assert_eq!(digits.collect::<Vec<_>>(), [1, 2, 3, 4]);
+        // Exhausted adapters stay exhausted.
+        assert_eq!(digits.next(), None);
+        drop(digits);
+
+        assert_eq!(tokens.collect::<String>(), "abc");
+        assert_eq!(tokens.buffer_len(), 0);
+    }
+
+    #[test]
+    fn take_while_map_at_end() {
+        let mut tokens = StreamTokens::new("12".chars());
+        assert_eq!(
+            tokens.take_while_map(|c| c.to_digit(10)).as_iter().count(),
+            2
+        );
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn skip_until_found() {
+        let mut tokens = StreamTokens::new("abc\ndef".chars());
+
+        assert!(tokens.skip_until(|&c| c == '\n'));
+        assert_eq!(tokens.collect::<String>(), "\ndef");
+        assert!(tokens.peak_buffer_len() <= 1);
+    }
+
+    #[test]
+    fn skip_until_not_found() {
+        let mut tokens = StreamTokens::new(core::iter::repeat_n('a', 1000));
+
+        assert!(!tokens.skip_until(|&c| c == '\n'));
+        assert_eq!(tokens.next(), None);
+        assert!(tokens.peak_buffer_len() <= 1);
+    }
+
+    #[test]
+    fn recover_to_next_statement() {
+        let mut tokens = StreamTokens::new("let = ;let x = 1;".chars());
+
+        // A parser fails on the first statement.
+        assert!(tokens.tokens("let ".chars()));
+        assert!(!tokens.tokens("x".chars()));
+
+        assert_eq!(tokens.recover_to(|&c| c == ';'), 2);
+        assert!(tokens.token(';'));
+        assert!(tokens.tokens("let x".chars()));
+
+        // Nothing to discard when already at the sync token.
+        tokens.take(4).consume();
+        assert_eq!(tokens.recover_to(|&c| c == ';'), 0);
+        assert!(tokens.token(';'));
+
+        // Up to the end without a sync token.
+        assert_eq!(tokens.recover_to(|&c| c == ';'), 0);
+    }
+
+    #[test]
+    fn peek_pair_positions() {
+        let mut tokens = StreamTokens::new("abc".chars());
+
+        assert_eq!(tokens.peek_pair(), (Some('a'), Some('b')));
+        assert_eq!(tokens.offset(), 0);
+
+        tokens.take(2).consume();
+        // One item before the end.
+        assert_eq!(tokens.peek_pair(), (Some('c'), None));
+        assert_eq!(tokens.offset(), 2);
+
+        tokens.next();
+        assert_eq!(tokens.peek_pair(), (None, None));
+        assert_eq!(tokens.offset(), 3);
+    }
+
+    #[test]
+    fn until_frames_at_sentinel() {
+        let mut tokens = StreamTokens::new([1, 2, 0, 3, 4].into_iter());
+        let start = tokens.location();
+        assert_eq!(tokens.next(), Some(1));
+        // Already buffered past the sentinel.
+        assert_eq!(tokens.fill(4), 4);
+
+        let mut frame = tokens.until(|&b| b == 0);
+        assert_eq!(frame.next(), Some(2));
+        assert_eq!(frame.next(), None);
+        assert_eq!(frame.next(), None);
+
+        frame.set_location(start);
+        assert_eq!(frame.collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    fn recognize_identifier() {
+        let mut tokens = StreamTokens::new("let x_1 = 2".chars());
+        assert!(tokens.tokens("let ".chars()));
+
+        let (ok, mut ident) = tokens.recognize(|t| {
+            t.next().filter(|c| c.is_alphabetic())?;
+            t.skip_while(|c| c.is_alphanumeric() || *c == '_');
+            Some(())
+        });
+        assert_eq!(ok, Some(()));
+        assert_eq!(ident.collect::<String>(), "x_1");
+        drop(ident);
+        assert_eq!(tokens.collect::<String>(), " = 2");
+    }
+
+    #[test]
+    fn spanned_number() {
+        let mut tokens = StreamTokens::new("  123 + 4".chars());
+        tokens.skip_while(|c| c.is_whitespace());
+
+        let (n, from, to) =
+            tokens.spanned(|t| t.take_while(char::is_ascii_digit).parse::<u32, String>());
+        assert_eq!(n, Ok(123));
+        assert_eq!(tokens.live_locations(), 2);
+
+        let span = tokens.slice(from, to).collect::<String>();
+        assert_eq!(span, "123");
+        assert_eq!(tokens.collect::<String>(), " + 4");
+    }
+
+    fn digit_or_eof(
+        t: &mut StreamTokens<core::str::Chars<'_>, VecDeque<char>>,
+    ) -> Result<u32, &'static str> {
+        match t.next() {
+            Some(c) => c.to_digit(10).ok_or("not a digit"),
+            None => Err("eof"),
+        }
+    }
+
+    #[test]
+    fn parse_until_terminator() {
+        // Terminator straight away.
+        let mut tokens = StreamTokens::new(";1".chars());
+        assert_eq!(
+            tokens.parse_until(digit_or_eof, |t| t.token(';')),
+            Ok(vec![])
+        );
+        assert_eq!(tokens.next(), Some('1'));
+
+        let mut tokens = StreamTokens::new("123;;".chars());
+        assert_eq!(
+            tokens.parse_until(digit_or_eof, |t| t.token(';')),
+            Ok(vec![1, 2, 3])
+        );
+        assert_eq!(tokens.collect::<String>(), ";");
+    }
+
+    #[test]
+    fn parse_until_eof_is_error() {
+        let mut tokens = StreamTokens::new("12".chars());
+        // A terminator that partly matches must not consume.
+        assert_eq!(
+            tokens.parse_until(digit_or_eof, |t| t.tokens("2;".chars())),
+            Err("eof")
+        );
+        assert_eq!(tokens.collect::<String>(), "12");
+    }
+
+    #[test]
+    fn parse_until_stops_without_progress() {
+        let mut tokens = StreamTokens::new("ab".chars());
+        assert_eq!(
+            tokens.parse_until(|t| Ok::<_, ()>(t.next()), |t| t.token(';')),
+            Ok(vec![Some('a'), Some('b'), None])
+        );
+    }
+
+    #[test]
+    fn alt_rewinds_between_alternatives() {
+        let mut tokens = StreamTokens::new("1234;".chars());
+        let mut first_calls = 0;
+        let parsed = tokens.alt(&mut [
+            // Consumes "1234" before failing on ';'.
+            &mut |t| {
+                first_calls += 1;
+                t.skip_while(char::is_ascii_digit);
+                t.token(',').then_some("list")
+            },
+            &mut |t| t.tokens("1234;".chars()).then_some("statement"),
+            &mut |_| unreachable!(),
+        ]);
+        assert_eq!(parsed, Some("statement"));
+        assert_eq!(first_calls, 1);
+        assert_eq!(tokens.next(), None);
+
+        let mut tokens = StreamTokens::new("xy".chars());
+        assert_eq!(tokens.alt::<()>(&mut [&mut |t| t.next().and(None)]), None);
+        assert_eq!(tokens.collect::<String>(), "xy");
+    }
+
+    #[test]
+    fn consume_if_match_and_mismatch() {
+        let mut tokens = StreamTokens::new("ab".chars());
+
+        let before = tokens.location();
+        assert_eq!(tokens.consume_if(|&c| c == 'b'), None);
+        assert!(tokens.is_at_location(&before));
+        drop(before);
+
+        assert_eq!(tokens.consume_if(|&c| c == 'a'), Some('a'));
+        assert_eq!(tokens.live_locations(), 0);
+        assert_eq!(tokens.collect::<String>(), "b");
+    }
+
+    #[test]
+    fn consume_one_of_set() {
+        let mut tokens = StreamTokens::new("(x".chars());
+        assert_eq!(tokens.consume_one_of(['(', '[']), Some('('));
+        assert_eq!(tokens.consume_one_of(['(', '[']), None);
+        assert_eq!(tokens.next(), Some('x'));
+        assert_eq!(tokens.consume_one_of(['x']), None);
+        assert_eq!(tokens.consume_one_of([]), None);
+    }
+
+    #[test]
+    fn take_while_indexed_exactly_four() {
+        let mut tokens = StreamTokens::new("123456".chars());
+
+        let mut seen = Vec::new();
+        let taken = tokens
+            .take_while_indexed(|idx, c| {
+                seen.push(idx);
+                idx < 4 && c.is_ascii_digit()
+            })
+            .collect::<String>();
+
+        assert_eq!(taken, "1234");
+        assert_eq!(seen, [0, 1, 2, 3, 4]);
+        assert_eq!(tokens.collect::<String>(), "56");
+    }
+
+    #[test]
+    fn take_while_indexed_rewind_within_run() {
+        let mut tokens = StreamTokens::new("abc".chars());
+        let mut run = tokens.take_while_indexed(|idx, _| idx < 2);
+
+        let loc = run.location();
+        assert_eq!(run.next(), Some('a'));
+        run.set_location(loc);
+        assert_eq!(run.collect::<String>(), "ab");
+    }
+
+    #[test]
+    fn separated_fold_no_items() {
+        let mut tokens = StreamTokens::new("x".chars());
+
+        let count = tokens.separated_fold(
+            0,
+            |t| t.next().filter(char::is_ascii_digit),
+            |t| t.token(','),
+            |acc, _| acc + 1,
+        );
+
+        assert_eq!(count, 0);
+        assert_eq!(tokens.next(), Some('x'));
+    }
+
+    #[test]
+    fn take_while_max_shorter_than_cap() {
+        let mut tokens = StreamTokens::new("42;".chars());
+        let (mut digits, capped) = tokens.take_while_max(8, char::is_ascii_digit);
+        assert_eq!(digits.collect::<String>(), "42");
+        assert!(!capped);
+        drop(digits);
+        assert_eq!(tokens.next(), Some(';'));
+
+        let (mut digits, capped) = tokens.take_while_max(8, char::is_ascii_digit);
+        assert_eq!(digits.next(), None);
+        assert!(!capped);
+    }
+
+    #[test]
+    fn take_while_max_hits_cap() {
+        let input = "9".repeat(1000);
+        let mut tokens = StreamTokens::new(input.chars());
+        let (mut digits, capped) = tokens.take_while_max(20, char::is_ascii_digit);
+        assert_eq!(digits.as_iter().count(), 20);
+        assert!(capped);
+        drop(digits);
+        assert!(tokens.peak_buffer_len() <= 20);
+        assert_eq!(tokens.location().offset(), 20);
+
+        // A run exactly as long as the cap also reports it.
+        let mut tokens = StreamTokens::new("abc".chars());
+        let (_, capped) = tokens.take_while_max(3, char::is_ascii_alphabetic);
+        assert!(capped);
+        let (_, capped) = tokens.take_while_max(0, char::is_ascii_alphabetic);
+        assert!(capped);
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum PairError {
+        Open,
+        Close,
+    }
+
+    /// `(` digit `)` or nested pairs, such as `((1))`.
+    fn paren_digit(
+        t: &mut StreamTokens<core::str::Chars<'_>, VecDeque<char>>,
+    ) -> Result<u32, PairError> {
+        t.try_rewinding(|t| {
+            if !t.token('(') {
+                return Err(PairError::Open);
+            }
+            let inner = match t.peek() {
+                Some('(') => paren_digit(t)?,
+                _ => t
+                    .next()
+                    .and_then(|c| c.to_digit(10))
+                    .ok_or(PairError::Close)?,
+            };
+            if !t.token(')') {
+                return Err(PairError::Close);
+            }
+            Ok(inner)
+        })
+    }
+
+    #[test]
+    fn try_rewinding_ok_advances() {
+        let mut tokens = StreamTokens::new("(4)(5)".chars());
+        assert_eq!(paren_digit(&mut tokens), Ok(4));
+        assert_eq!(tokens.location().offset(), 3);
+        assert_eq!(tokens.buffer_len(), 0);
+        assert_eq!(paren_digit(&mut tokens), Ok(5));
+        assert_eq!(paren_digit(&mut tokens), Err(PairError::Open));
+    }
+
+    #[test]
+    fn try_rewinding_err_restores() {
+        for input in ["(4]", "4)", "(x)", "("] {
+            let mut tokens = StreamTokens::new(input.chars());
+            assert!(paren_digit(&mut tokens).is_err());
+            assert_eq!(tokens.collect::<String>(), input);
+        }
+    }
+
+    #[test]
+    fn try_rewinding_nested() {
+        let mut tokens = StreamTokens::new("((7))((7)]".chars());
+        assert_eq!(paren_digit(&mut tokens), Ok(7));
+        // The inner pair parses but the outer one fails, rewinding both.
+        assert_eq!(paren_digit(&mut tokens), Err(PairError::Close));
+        assert_eq!(tokens.location().offset(), 5);
+        assert_eq!(tokens.collect::<String>(), "((7)]");
+    }
+}
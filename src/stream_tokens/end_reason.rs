@@ -0,0 +1,110 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use alloc::vec::Vec;
+use yap::Tokens;
+
+/// What ended [`StreamTokens::parse_while_more`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EndReason {
+    /// The terminator matched and was consumed.
+    Terminator,
+    /// The stream ended.
+    Eof,
+    /// The item parser returned [`None`] before either of the others.
+    NoItem,
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Parse items with `item` until `stop` matches or the stream ends, returning them along with which of those ended parsing.
+    ///
+    /// `stop` is tried before each item and doesn't consume anything unless it returns `true`.
+    /// If `item` returns [`None`] first, what it consumed is rewound and [`EndReason::NoItem`] is returned.
+    /// Items parsed before are kept either way.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{EndReason, Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("ab;cd".chars());
+    /// let (items, end) = tokens.parse_while_more(|t| t.next(), |t| t.token(';'));
+    /// assert_eq!((items, end), (vec!['a', 'b'], EndReason::Terminator));
+    /// let (items, end) = tokens.parse_while_more(|t| t.next(), |t| t.token(';'));
+    /// assert_eq!((items, end), (vec!['c', 'd'], EndReason::Eof));
+    /// ```
+    pub fn parse_while_more<T, F, S>(&mut self, mut item: F, mut stop: S) -> (Vec<T>, EndReason)
+    where
+        F: FnMut(&mut Self) -> Option<T>,
+        S: FnMut(&mut Self) -> bool,
+    {
+        let mut items = Vec::new();
+        loop {
+            if self.optional(|t| stop(t).then_some(())).is_some() {
+                return (items, EndReason::Terminator);
+            }
+            if self.fill(1) == 0 {
+                return (items, EndReason::Eof);
+            }
+            match self.optional(&mut item) {
+                Some(out) => items.push(out),
+                None => return (items, EndReason::NoItem),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    fn digit(
+        t: &mut StreamTokens<core::str::Chars<'_>, alloc::collections::VecDeque<char>>,
+    ) -> Option<u32> {
+        let d = t.next()?.to_digit(10)?;
+        t.token(',');
+        Some(d)
+    }
+
+    #[test]
+    fn parse_while_more_terminator() {
+        let mut tokens = StreamTokens::new("1,2,3;rest".chars());
+        let (items, end) = tokens.parse_while_more(digit, |t| t.token(';'));
+        assert_eq!(items, [1, 2, 3]);
+        assert_eq!(end, EndReason::Terminator);
+        assert_eq!(tokens.collect::<String>(), "rest");
+
+        // A partial terminator match is not consumed.
+        let mut tokens = StreamTokens::new("1,2;".chars());
+        let (items, end) = tokens.parse_while_more(digit, |t| t.tokens("2;2".chars()));
+        assert_eq!(items, [1, 2]);
+        assert_eq!(end, EndReason::NoItem);
+        assert_eq!(tokens.collect::<String>(), ";");
+    }
+
+    #[test]
+    fn parse_while_more_eof() {
+        let mut tokens = StreamTokens::new("1,2,3".chars());
+        let (items, end) = tokens.parse_while_more(digit, |t| t.token(';'));
+        assert_eq!(items, [1, 2, 3]);
+        assert_eq!(end, EndReason::Eof);
+
+        let mut tokens = StreamTokens::new("".chars());
+        let (items, end) = tokens.parse_while_more(digit, |t| t.token(';'));
+        assert!(items.is_empty());
+        assert_eq!(end, EndReason::Eof);
+    }
+
+    #[test]
+    fn parse_while_more_no_item() {
+        let mut tokens = StreamTokens::new("1,x".chars());
+        let (items, end) = tokens.parse_while_more(digit, |t| t.token(';'));
+        assert_eq!(items, [1]);
+        assert_eq!(end, EndReason::NoItem);
+        assert_eq!(tokens.next(), Some('x'));
+    }
+}
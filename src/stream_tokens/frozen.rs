@@ -0,0 +1,159 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use alloc::rc::Rc;
+use yap::{TokenLocation, Tokens};
+
+/// [`Tokens`] over a whole stream held in memory, created by [`StreamTokens::freeze`].
+///
+/// The items are shared, so cloning is cheap and each clone can be parsed independently.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrozenTokens<Item> {
+    items: Rc<[Item]>,
+    cursor: usize,
+}
+
+/// This implements [`TokenLocation`] for [`FrozenTokens`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrozenTokensLocation(usize);
+
+impl TokenLocation for FrozenTokensLocation {
+    fn offset(&self) -> usize {
+        self.0
+    }
+}
+
+impl<Item> FrozenTokens<Item> {
+    /// All of the stream's items, including those before the current location.
+    pub fn as_slice(&self) -> &[Item] {
+        &self.items
+    }
+}
+
+impl<Item: Clone> Tokens for FrozenTokens<Item> {
+    type Item = Item;
+
+    type Location = FrozenTokensLocation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.items.get(self.cursor)?.clone();
+        self.cursor += 1;
+        Some(item)
+    }
+
+    fn location(&self) -> Self::Location {
+        FrozenTokensLocation(self.cursor)
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        self.cursor = location.0;
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.cursor == location.0
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Turn the stream into [`FrozenTokens`] if all of it is buffered, which is the case once a location from its start
+    /// has been held until the wrapped iterator returned [`None`]. Otherwise the tokens are given back unchanged.
+    ///
+    /// The frozen tokens are at the same offset as the cursor was.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abc".chars());
+    /// let start = tokens.location();
+    /// tokens.consume();
+    /// tokens.set_location(start);
+    ///
+    /// let mut first = tokens.freeze().unwrap();
+    /// let mut second = first.clone();
+    /// assert_eq!(first.collect::<String>(), "abc");
+    /// assert_eq!(second.collect::<String>(), "abc");
+    /// ```
+    // The tokens are given back so that parsing can go on streaming.
+    #[allow(clippy::result_large_err)]
+    pub fn freeze(self) -> Result<FrozenTokens<I::Item>, Self> {
+        if !self.iter.exhausted || self.buffer.oldest_elem_cursor != 0 {
+            return Err(self);
+        }
+        let items = (0..self.buffer.len)
+            .filter_map(|idx| self.buffer.elements.get(idx))
+            .collect();
+        Ok(FrozenTokens {
+            items,
+            cursor: self.cursor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::String, vec::Vec};
+
+    #[test]
+    fn freeze_for_two_passes() {
+        let mut tokens = StreamTokens::new("12,34".chars());
+        let start = tokens.location();
+        tokens.consume();
+        tokens.set_location(start);
+        let frozen = tokens.freeze().unwrap();
+        assert_eq!(frozen.as_slice(), ['1', '2', ',', '3', '4']);
+
+        let mut numbers = frozen.clone();
+        let parsed = numbers
+            .sep_by(
+                |t| {
+                    t.take_while(char::is_ascii_digit)
+                        .parse::<u8, String>()
+                        .ok()
+                },
+                |t| t.token(','),
+            )
+            .as_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(parsed, [12, 34]);
+
+        let mut digits = frozen;
+        assert_eq!(digits.as_iter().filter(char::is_ascii_digit).count(), 4);
+    }
+
+    #[test]
+    fn freeze_keeps_offset() {
+        let mut tokens = StreamTokens::new("abc".chars());
+        let start = tokens.location();
+        tokens.consume();
+        tokens.set_location(start);
+        tokens.next();
+        let mut frozen = tokens.freeze().unwrap();
+        assert_eq!(frozen.location().offset(), 1);
+        assert_eq!(frozen.collect::<String>(), "bc");
+    }
+
+    #[test]
+    fn freeze_needs_whole_stream() {
+        // Not read to the end.
+        let mut tokens = StreamTokens::new("abc".chars());
+        let start = tokens.location();
+        tokens.next();
+        let mut tokens = tokens.freeze().unwrap_err();
+        tokens.set_location(start);
+        assert_eq!(tokens.collect::<String>(), "abc");
+
+        // Start no longer buffered.
+        let mut tokens = StreamTokens::new("abc".chars());
+        tokens.next();
+        let start = tokens.location();
+        tokens.consume();
+        tokens.set_location(start);
+        assert!(tokens.freeze().is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use core::fmt;
+use yap::Tokens;
+
+/// [`Tokens`] calling a function on each item of a [`StreamTokens`], returned by [`StreamTokens::inspect`].
+pub struct InspectTokens<T, F> {
+    tokens: T,
+    f: F,
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for InspectTokens<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InspectTokens")
+            .field("tokens", &self.tokens)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, F> InspectTokens<T, F> {
+    /// The wrapped tokens.
+    pub fn into_inner(self) -> T {
+        self.tokens
+    }
+}
+
+impl<T, F> Tokens for InspectTokens<T, F>
+where
+    T: Tokens,
+    F: FnMut(&T::Item),
+{
+    type Item = T::Item;
+
+    type Location = T::Location;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.tokens.next()?;
+        (self.f)(&item);
+        Some(item)
+    }
+
+    fn location(&self) -> Self::Location {
+        self.tokens.location()
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        self.tokens.set_location(location);
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.tokens.is_at_location(location)
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Call `f` on each item as it is returned, like [`Iterator::inspect`].
+    ///
+    /// Items returned again after rewinding are passed to `f` again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{StreamTokens, Tokens};
+    ///
+    /// let mut seen = String::new();
+    /// let mut tokens = StreamTokens::new("ab".chars()).inspect(|&c| seen.push(c));
+    /// let start = tokens.location();
+    /// tokens.next();
+    /// tokens.set_location(start);
+    /// tokens.consume();
+    /// drop(tokens);
+    /// assert_eq!(seen, "aab");
+    /// ```
+    pub fn inspect<F>(self, f: F) -> InspectTokens<Self, F>
+    where
+        F: FnMut(&I::Item),
+    {
+        InspectTokens { tokens: self, f }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{rc::Rc, vec::Vec};
+    use core::cell::RefCell;
+
+    #[test]
+    fn inspect_fresh_and_replayed() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&seen);
+        let mut tokens =
+            StreamTokens::new([1, 2, 3].into_iter()).inspect(move |&n| log.borrow_mut().push(n));
+
+        assert_eq!(tokens.next(), Some(1));
+        let loc = tokens.location();
+        assert_eq!(tokens.next(), Some(2));
+        assert_eq!(tokens.next(), Some(3));
+        tokens.set_location(loc);
+        assert_eq!(tokens.next(), Some(2));
+        assert_eq!(tokens.next(), Some(3));
+        assert_eq!(tokens.next(), None);
+
+        assert_eq!(*seen.borrow(), [1, 2, 3, 2, 3]);
+    }
+
+    #[test]
+    fn inspect_does_not_alter_stream() {
+        let mut count = 0;
+        let mut tokens = StreamTokens::new("a1b2".chars()).inspect(|_| count += 1);
+        assert!(tokens.token('a'));
+        assert_eq!(tokens.take_while(char::is_ascii_digit).as_iter().count(), 1);
+        assert_eq!(tokens.into_inner().collect::<alloc::string::String>(), "b2");
+        assert_eq!(count, 3);
+    }
+}
@@ -0,0 +1,169 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use std::{io, vec::Vec};
+
+/// [`io::Read`] over the bytes of an iterator.
+struct IterRead<It>(It);
+
+impl<It: Iterator<Item = u8>> io::Read for IterRead<It> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+        for (slot, byte) in buf.iter_mut().zip(&mut self.0) {
+            *slot = byte;
+            n += 1;
+        }
+        Ok(n)
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator<Item = u8>,
+    Buf: StreamTokensBuffer<u8>,
+{
+    /// Hand the rest of the stream to something expecting [`io::Read`], for example after parsing a header.
+    ///
+    /// The reader yields the bytes after the cursor that are already buffered, then continues with the wrapped iterator.
+    /// Bytes before the cursor can no longer be rewound to and are dropped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::Read;
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new(b"MAGIC payload".iter().copied());
+    /// assert!(tokens.tokens(b"MAGIC ".iter().copied()));
+    ///
+    /// let mut rest = String::new();
+    /// tokens.into_read().read_to_string(&mut rest).unwrap();
+    /// assert_eq!(rest, "payload");
+    /// ```
+    pub fn into_read(self) -> impl io::Read {
+        let buffered = (self.cursor - self.buffer.oldest_elem_cursor..self.buffer.len)
+            .filter_map(|idx| self.buffer.elements.get(idx))
+            .collect::<Vec<_>>();
+        IterRead(buffered.into_iter().chain(self.iter))
+    }
+
+    /// Write the buffered bytes before the cursor that no live location needs to `w`, then drop them from the buffer.
+    /// Returns how many bytes were written.
+    ///
+    /// Consumed bytes stay buffered only while a location needs them, so hold one from the start of what should be written
+    /// and drop it before flushing. Bytes already dropped from the buffer aren't written. If writing fails nothing is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new(b"HDR body".iter().copied());
+    /// let start = tokens.location();
+    /// assert!(tokens.tokens(b"HDR ".iter().copied()));
+    /// drop(start);
+    ///
+    /// let mut log = Vec::new();
+    /// assert_eq!(tokens.flush_consumed_to(&mut log).unwrap(), 4);
+    /// assert_eq!(log, b"HDR ");
+    /// ```
+    pub fn flush_consumed_to<W: io::Write>(&mut self, w: &mut W) -> io::Result<usize> {
+        let min = self.oldest_needed();
+        let consumed = (0..min.saturating_sub(self.buffer.oldest_elem_cursor))
+            .filter_map(|idx| self.buffer.elements.get(idx))
+            .collect::<Vec<_>>();
+        w.write_all(&consumed)?;
+        self.buffer.drain_to(min);
+        Ok(consumed.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use yap::Tokens;
+
+    #[test]
+    fn into_read_after_header() {
+        let input = b"HDR1the rest of the body";
+        let mut tokens = StreamTokens::new(input.iter().copied());
+
+        assert_eq!(tokens.take(4).collect::<Vec<_>>(), b"HDR1");
+
+        let mut rest = Vec::new();
+        tokens.into_read().read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"the rest of the body");
+    }
+
+    #[test]
+    fn into_read_starts_with_buffered_bytes() {
+        let mut tokens = StreamTokens::new(b"HDR1tail".iter().copied());
+
+        // Read past the header then rewind so the start of the tail is buffered.
+        tokens.take(4).consume();
+        let tail = tokens.location();
+        tokens.take(2).consume();
+        tokens.set_location(tail);
+
+        let mut rest = Vec::new();
+        let mut reader = tokens.into_read();
+        // Small reads work across the buffered and streamed parts.
+        let mut chunk = [0; 3];
+        while let Ok(n @ 1..) = reader.read(&mut chunk) {
+            rest.extend_from_slice(&chunk[..n]);
+        }
+        assert_eq!(rest, b"tail");
+    }
+
+    #[test]
+    fn flush_consumed_header() {
+        let mut tokens = StreamTokens::new(b"LEN:3;abc;rest".iter().copied());
+        let mut sink = Vec::new();
+
+        let header_start = tokens.location();
+        assert!(tokens.tokens(b"LEN:".iter().copied()));
+        // A live location keeps what is after it buffered.
+        let len_start = tokens.location();
+        assert_eq!(tokens.next(), Some(b'3'));
+        drop(header_start);
+        assert_eq!(tokens.flush_consumed_to(&mut sink).unwrap(), 4);
+        assert_eq!(sink, b"LEN:");
+        assert_eq!(tokens.buffer_len(), 1);
+
+        assert!(tokens.tokens(b";abc;".iter().copied()));
+        drop(len_start);
+        assert_eq!(tokens.flush_consumed_to(&mut sink).unwrap(), 6);
+        assert_eq!(sink, b"LEN:3;abc;");
+        assert_eq!(tokens.buffer_len(), 0);
+        assert_eq!(tokens.flush_consumed_to(&mut sink).unwrap(), 0);
+
+        // Bytes read ahead stay buffered.
+        let rest = tokens.location();
+        assert_eq!(tokens.next(), Some(b'r'));
+        tokens.set_location(rest);
+        assert_eq!(tokens.flush_consumed_to(&mut sink).unwrap(), 0);
+        assert_eq!(tokens.buffer_len(), 1);
+        assert_eq!(tokens.collect::<Vec<_>>(), b"rest");
+    }
+
+    #[test]
+    fn flush_consumed_keeps_bytes_on_error() {
+        struct Failing;
+        impl io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::Other.into())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut tokens = StreamTokens::new(b"ab".iter().copied());
+        let start = tokens.location();
+        tokens.next();
+        drop(start);
+        assert!(tokens.flush_consumed_to(&mut Failing).is_err());
+        let mut sink = Vec::new();
+        assert_eq!(tokens.flush_consumed_to(&mut sink).unwrap(), 1);
+        assert_eq!(sink, b"a");
+    }
+}
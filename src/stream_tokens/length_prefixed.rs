@@ -0,0 +1,187 @@
+use super::{StreamTokens, StreamTokensBuffer, StreamTokensLocation};
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StreamTokens::with_length_prefix`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthPrefixError {
+    /// The length couldn't be read.
+    NoLength,
+    /// The stream ended after this many tokens of the body.
+    Truncated(usize),
+}
+
+impl fmt::Display for LengthPrefixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LengthPrefixError::NoLength => write!(f, "expected length prefix"),
+            LengthPrefixError::Truncated(n) => write!(f, "body truncated after {n} tokens"),
+        }
+    }
+}
+
+impl core::error::Error for LengthPrefixError {}
+
+/// The next `len` tokens of a [`StreamTokens`], all of which are buffered.
+///
+/// Created by [`StreamTokens::take_length_prefixed`].
+#[derive(Debug)]
+pub struct LengthPrefixed<'a, I: Iterator, Buf> {
+    tokens: &'a mut StreamTokens<I, Buf>,
+    end: usize,
+}
+
+impl<I, Buf> Tokens for LengthPrefixed<'_, I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    type Item = I::Item;
+
+    type Location = StreamTokensLocation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.tokens.cursor >= self.end {
+            return None;
+        }
+        self.tokens.next()
+    }
+
+    fn location(&self) -> Self::Location {
+        self.tokens.location()
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        self.tokens.set_location(location);
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.tokens.is_at_location(location)
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Buffer the next `len` tokens and return them if the stream doesn't end first.
+    /// Otherwise nothing is consumed and the number of tokens that are available is returned.
+    ///
+    /// Fails if `len` is more than fits in a bounded buffer.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abc".chars());
+    /// assert_eq!(tokens.take_length_prefixed(4).err(), Some(3));
+    /// let two = tokens.take_length_prefixed(2).unwrap().collect::<String>();
+    /// assert_eq!(two, "ab");
+    /// ```
+    pub fn take_length_prefixed(
+        &mut self,
+        len: usize,
+    ) -> Result<LengthPrefixed<'_, I, Buf>, usize> {
+        let available = self.fill(len);
+        if available < len {
+            return Err(available);
+        }
+        Ok(LengthPrefixed {
+            end: self.cursor + len,
+            tokens: self,
+        })
+    }
+
+    /// Read a length with `read_len` then run `body` over exactly that many tokens.
+    /// Any of them `body` doesn't consume are skipped.
+    ///
+    /// On error the location is reset to before the length and `body` isn't run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new([2, 0, b'h', b'i', 1, 0, b'!'].into_iter());
+    /// let read_len = |t: &mut StreamTokens<_, _>| {
+    ///     Some(u16::from_le_bytes([t.next()?, t.next()?]) as usize)
+    /// };
+    ///
+    /// let first = tokens.with_length_prefix(read_len, |body| body.collect::<Vec<u8>>());
+    /// assert_eq!(first.as_deref(), Ok(&b"hi"[..]));
+    /// let second = tokens.with_length_prefix(read_len, |body| body.next());
+    /// assert_eq!(second, Ok(Some(b'!')));
+    /// ```
+    pub fn with_length_prefix<T, L, F>(
+        &mut self,
+        read_len: L,
+        body: F,
+    ) -> Result<T, LengthPrefixError>
+    where
+        L: FnOnce(&mut Self) -> Option<usize>,
+        F: FnOnce(&mut LengthPrefixed<'_, I, Buf>) -> T,
+    {
+        let from = self.location();
+        let Some(len) = read_len(self) else {
+            self.set_location(from);
+            return Err(LengthPrefixError::NoLength);
+        };
+        let mut block = match self.take_length_prefixed(len) {
+            Ok(block) => block,
+            Err(available) => {
+                self.set_location(from);
+                return Err(LengthPrefixError::Truncated(available));
+            }
+        };
+        let out = body(&mut block);
+        block.consume();
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{collections::VecDeque, vec, vec::Vec};
+
+    fn read_len<I: Iterator<Item = u8>>(
+        tokens: &mut StreamTokens<I, VecDeque<u8>>,
+    ) -> Option<usize> {
+        Some(u16::from_be_bytes([tokens.next()?, tokens.next()?]).into())
+    }
+
+    #[test]
+    fn with_length_prefix_body() {
+        let mut tokens = StreamTokens::new([0, 3, 1, 2, 3, 0, 2, 4, 5, 9].into_iter());
+
+        // Unread body tokens are skipped.
+        assert_eq!(
+            tokens.with_length_prefix(read_len, |body| body.next()),
+            Ok(Some(1))
+        );
+        assert_eq!(
+            tokens.with_length_prefix(read_len, |body| body.collect::<Vec<_>>()),
+            Ok(vec![4, 5])
+        );
+        assert_eq!(tokens.next(), Some(9));
+        assert_eq!(
+            tokens.with_length_prefix(read_len, |_| ()),
+            Err(LengthPrefixError::NoLength)
+        );
+    }
+
+    #[test]
+    fn with_length_prefix_truncated() {
+        let mut tokens = StreamTokens::new([0, 4, 1, 2, 3].into_iter());
+        assert_eq!(
+            tokens.with_length_prefix(read_len, |_| unreachable!()),
+            Err(LengthPrefixError::Truncated(3))
+        );
+        // Nothing consumed.
+        assert_eq!(tokens.collect::<Vec<_>>(), [0, 4, 1, 2, 3]);
+    }
+}
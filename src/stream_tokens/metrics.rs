@@ -0,0 +1,116 @@
+use super::{StreamTokens, StreamTokensBuffer};
+
+/// Snapshot of a [`StreamTokens`]' introspection counters, returned by [`StreamTokens::metrics`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StreamMetrics {
+    /// See [`StreamTokens::buffer_len`].
+    pub buffer_len: usize,
+    /// See [`StreamTokens::peak_buffer_len`].
+    pub peak_buffer_len: usize,
+    /// See [`StreamTokens::items_pulled`].
+    pub items_pulled: usize,
+    /// See [`StreamTokens::live_locations`].
+    pub live_locations: usize,
+    /// See [`StreamTokens::live_anchors`].
+    pub live_anchors: usize,
+    /// See [`StreamTokens::max_location_depth`].
+    pub max_location_depth: usize,
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Number of items pulled from the wrapped iterator so far, including any prefix from [`Self::with_prefix`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abc".chars());
+    /// let start = tokens.location();
+    /// tokens.take(2).consume();
+    /// tokens.set_location(start);
+    /// assert_eq!(tokens.items_pulled(), 2);
+    /// ```
+    pub fn items_pulled(&self) -> usize {
+        self.buffer.oldest_elem_cursor + self.buffer.len
+    }
+
+    /// All of the introspection counters at once, for example to log one line about the parser's health.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abc".chars());
+    /// let _start = tokens.location();
+    /// tokens.next();
+    /// let metrics = tokens.metrics();
+    /// assert_eq!(metrics.buffer_len, 1);
+    /// assert_eq!(metrics.live_locations, 1);
+    /// ```
+    pub fn metrics(&self) -> StreamMetrics {
+        StreamMetrics {
+            buffer_len: self.buffer_len(),
+            peak_buffer_len: self.peak_buffer_len(),
+            items_pulled: self.items_pulled(),
+            live_locations: self.live_locations(),
+            live_anchors: self.live_anchors(),
+            max_location_depth: self.max_location_depth(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yap::Tokens;
+
+    #[test]
+    fn metrics_after_scripted_reads() {
+        let mut tokens = StreamTokens::new("abcdef".chars());
+        assert_eq!(tokens.metrics(), StreamMetrics::default());
+
+        let anchor = tokens.anchor();
+        tokens.take(2).consume();
+        let mid = tokens.location();
+        tokens.take(3).consume();
+        assert_eq!(
+            tokens.metrics(),
+            StreamMetrics {
+                buffer_len: 5,
+                peak_buffer_len: 5,
+                items_pulled: 5,
+                live_locations: 1,
+                live_anchors: 1,
+                max_location_depth: 2,
+            }
+        );
+
+        drop(anchor);
+        tokens.set_location(mid);
+        tokens.next();
+        tokens.next();
+        assert_eq!(
+            tokens.metrics(),
+            StreamMetrics {
+                // Items nothing needs are only dropped when the next is pulled.
+                buffer_len: 5,
+                peak_buffer_len: 5,
+                items_pulled: 5,
+                live_locations: 0,
+                live_anchors: 0,
+                max_location_depth: 2,
+            }
+        );
+
+        tokens.take(3).consume();
+        assert_eq!(tokens.metrics().items_pulled, 6);
+        assert_eq!(tokens.metrics().buffer_len, 0);
+    }
+}
@@ -0,0 +1,112 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use yap::Tokens;
+
+/// [`Tokens`] yielding a single `\n` for each `\r\n`, `\r` or `\n` of a [`StreamTokens`],
+/// returned by [`StreamTokens::normalize_newlines`].
+///
+/// Locations are those of the wrapped tokens, so a `\r\n` spans two offsets.
+#[derive(Debug)]
+pub struct NormalizedTokens<T> {
+    tokens: T,
+}
+
+impl<T> NormalizedTokens<T> {
+    /// The wrapped tokens.
+    pub fn into_inner(self) -> T {
+        self.tokens
+    }
+}
+
+impl<T> Tokens for NormalizedTokens<T>
+where
+    T: Tokens,
+    T::Item: PartialEq + From<u8>,
+{
+    type Item = T::Item;
+
+    type Location = T::Location;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.tokens.next()?;
+        if item != T::Item::from(b'\r') {
+            return Some(item);
+        }
+        // Look ahead one item for the `\n` of a `\r\n`.
+        let after = self.tokens.location();
+        if self.tokens.next() != Some(T::Item::from(b'\n')) {
+            self.tokens.set_location(after);
+        }
+        Some(T::Item::from(b'\n'))
+    }
+
+    fn location(&self) -> Self::Location {
+        self.tokens.location()
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        self.tokens.set_location(location);
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.tokens.is_at_location(location)
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone + PartialEq + From<u8>,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Read `\r\n` and `\r` line endings as `\n`, for text from any platform.
+    /// Works for streams of [`char`] or [`u8`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{StreamTokens, Tokens};
+    ///
+    /// let mut tokens = StreamTokens::new("a\r\nb\rc\n".chars()).normalize_newlines();
+    /// assert_eq!(tokens.collect::<String>(), "a\nb\nc\n");
+    /// ```
+    pub fn normalize_newlines(self) -> NormalizedTokens<Self> {
+        NormalizedTokens { tokens: self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::String, vec::Vec};
+    use yap::TokenLocation;
+
+    #[test]
+    fn mixed_line_endings() {
+        let mut tokens = StreamTokens::new("1\r\n2\r3\n\r\r\n4\r".chars()).normalize_newlines();
+        assert_eq!(tokens.collect::<String>(), "1\n2\n3\n\n\n4\n");
+
+        let mut tokens = StreamTokens::new(b"x\r\ny".iter().copied()).normalize_newlines();
+        assert_eq!(tokens.collect::<Vec<u8>>(), b"x\ny");
+    }
+
+    #[test]
+    fn rewind_across_normalized_pair() {
+        let mut tokens = StreamTokens::new("a\r\nb".chars()).normalize_newlines();
+        assert_eq!(tokens.next(), Some('a'));
+        let before = tokens.location();
+        assert_eq!(tokens.next(), Some('\n'));
+        // Both chars of the `\r\n` were consumed.
+        assert_eq!(tokens.location().offset(), 3);
+        assert_eq!(tokens.next(), Some('b'));
+
+        tokens.set_location(before.clone());
+        assert_eq!(tokens.next(), Some('\n'));
+        assert_eq!(tokens.next(), Some('b'));
+        assert_eq!(tokens.next(), None);
+
+        // The source still has the original line ending.
+        let mut inner = tokens.into_inner();
+        inner.set_location(before);
+        assert_eq!(inner.collect::<String>(), "\r\nb");
+    }
+}
@@ -0,0 +1,87 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use ::nom::{error::ErrorKind, Err, Parser};
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator<Item = u8>,
+    Buf: StreamTokensBuffer<u8> + core::ops::Deref<Target = [u8]>,
+{
+    /// Run a [`nom`] byte parser on the stream, advancing past what it consumed.
+    ///
+    /// The parser is given the buffered bytes after the current location as a slice so existing nom parsers can be reused.
+    /// Whenever it returns [`Err::Incomplete`] one more byte is pulled into the buffer and the parser is run again from the start,
+    /// so use nom's `streaming` parsers and be aware that long inputs are re-parsed once per byte.
+    /// Incomplete is only returned when the stream ends (or a bounded buffer is full) first.
+    ///
+    /// Nothing is consumed on error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use nom::{bytes::streaming::tag, number::streaming::le_u16, IResult};
+    /// use yap_streaming::{StreamTokens, Tokens};
+    ///
+    /// fn length(input: &[u8]) -> IResult<&[u8], u16> {
+    ///     let (input, _) = tag(&b"LEN"[..])(input)?;
+    ///     le_u16(input)
+    /// }
+    ///
+    /// let mut tokens = StreamTokens::<_, Vec<u8>>::new_with_buffer(b"LEN\x05\x01!".iter().copied());
+    /// assert_eq!(tokens.parse_nom(length), Ok(0x0105));
+    /// assert_eq!(tokens.next(), Some(b'!'));
+    /// ```
+    pub fn parse_nom<O, P>(&mut self, mut parser: P) -> Result<O, Err<ErrorKind>>
+    where
+        P: for<'b> Parser<&'b [u8], Output = O, Error = ::nom::error::Error<&'b [u8]>>,
+    {
+        loop {
+            let buffered = &self.buffer.elements[self.cursor - self.buffer.oldest_elem_cursor..];
+            let available = buffered.len();
+            let err = match parser.parse(buffered) {
+                Ok((rest, out)) => {
+                    self.cursor += available - rest.len();
+                    return Ok(out);
+                }
+                Err(err) => err.map(|e| e.code),
+            };
+            match err {
+                Err::Incomplete(needed) if !self.buffer_next() => {
+                    return Err(Err::Incomplete(needed))
+                }
+                Err::Incomplete(_) => {}
+                err => return Err(err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::nom::{bytes::streaming::tag, number::streaming::le_u16, IResult};
+    use alloc::vec::Vec;
+    use yap::Tokens;
+
+    fn record(input: &[u8]) -> IResult<&[u8], u16> {
+        let (input, _) = tag(&b"R:"[..])(input)?;
+        le_u16(input)
+    }
+
+    #[test]
+    fn parse_nom_records() {
+        let mut tokens =
+            StreamTokens::<_, Vec<u8>>::new_with_buffer(b"R:\x01\x00R:\xff\xffR:".iter().copied());
+        assert_eq!(tokens.parse_nom(record), Ok(1));
+        assert_eq!(tokens.parse_nom(record), Ok(u16::MAX));
+        assert!(matches!(tokens.parse_nom(record), Err(Err::Incomplete(_))));
+        // Nothing consumed by the failed parse.
+        assert_eq!(tokens.collect::<Vec<_>>(), b"R:");
+    }
+
+    #[test]
+    fn parse_nom_error_not_consumed() {
+        let mut tokens = StreamTokens::<_, Vec<u8>>::new_with_buffer(b"X:\x01\x00".iter().copied());
+        assert_eq!(tokens.parse_nom(record), Err(Err::Error(ErrorKind::Tag)));
+        assert_eq!(tokens.next(), Some(b'X'));
+    }
+}
@@ -0,0 +1,258 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use alloc::{collections::VecDeque, vec::Vec};
+use core::fmt::{self, Debug};
+use yap::Tokens;
+
+/// Multiplier of the polynomial hash.
+const BASE: u64 = 0x0000_0100_0000_01b3;
+
+/// Rabin-Karp hash over the last `window` items returned by [`Tokens::next()`](yap::Tokens::next).
+pub(super) struct RollingHash<I: Iterator> {
+    window: usize,
+    values: VecDeque<u64>,
+    hash: u64,
+    /// `BASE` to the power of `window - 1`, the weight of the oldest value.
+    top: u64,
+    to_u64: fn(&I::Item) -> u64,
+}
+
+impl<I: Iterator> Debug for RollingHash<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RollingHash")
+            .field("window", &self.window)
+            .field("hash", &self.hash)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I: Iterator> RollingHash<I> {
+    fn new(window: usize, to_u64: fn(&I::Item) -> u64) -> Self {
+        Self {
+            window,
+            values: VecDeque::with_capacity(window),
+            hash: 0,
+            top: (1..window).fold(1, |top, _| top.wrapping_mul(BASE)),
+            to_u64,
+        }
+    }
+
+    /// Keep hashing the same items pulled from a different source.
+    pub(super) fn with_source<J: Iterator<Item = I::Item>>(self) -> RollingHash<J> {
+        RollingHash {
+            window: self.window,
+            values: self.values,
+            hash: self.hash,
+            top: self.top,
+            to_u64: self.to_u64,
+        }
+    }
+
+    /// Roll `item` into the window, rolling the oldest value out if it is full.
+    pub(super) fn push(&mut self, item: &I::Item) {
+        if self.window == 0 {
+            return;
+        }
+        if self.values.len() == self.window {
+            let oldest = self.values.pop_front().unwrap_or_default();
+            self.hash = self.hash.wrapping_sub(oldest.wrapping_mul(self.top));
+        }
+        let value = (self.to_u64)(item);
+        self.values.push_back(value);
+        self.hash = self.hash.wrapping_mul(BASE).wrapping_add(value);
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone + Into<u64>,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Start maintaining a Rabin-Karp hash over the last `window` items returned by [`Tokens::next()`](yap::Tokens::next),
+    /// read with [`Self::current_hash`]. Enabling it again restarts the hash with the new window.
+    ///
+    /// Items returned again after rewinding to an earlier location are rolled in again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{StreamTokens, Tokens};
+    ///
+    /// let mut tokens = StreamTokens::new("abcabc".chars());
+    /// tokens.enable_rolling_hash(3);
+    /// tokens.take(3).consume();
+    /// let first = tokens.current_hash();
+    /// tokens.take(3).consume();
+    /// assert_eq!(tokens.current_hash(), first);
+    /// ```
+    pub fn enable_rolling_hash(&mut self, window: usize) {
+        self.rolling_hash = Some(RollingHash::new(window, |item: &I::Item| {
+            item.clone().into()
+        }));
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+{
+    /// Hash of the last items returned, or `0` if [`Self::enable_rolling_hash`] hasn't been called
+    /// or no items have been returned since.
+    ///
+    /// Until the window fills the hash covers every item returned so far.
+    pub fn current_hash(&self) -> u64 {
+        self.rolling_hash.as_ref().map_or(0, |rolling| rolling.hash)
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator<Item = u8>,
+    Buf: StreamTokensBuffer<u8>,
+{
+    /// Consume bytes up to a content-defined chunk boundary and return them, as used to deduplicate backups.
+    ///
+    /// The chunk ends after the first byte at which [`Self::current_hash`] `& mask` is `0`,
+    /// but has at least `min` bytes and at most `max` unless the stream ends first.
+    /// Call [`Self::enable_rolling_hash`] first, otherwise the hash is always `0` and chunks are `min` bytes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::StreamTokens;
+    ///
+    /// let data = (0..=255u8).cycle().take(2000).collect::<Vec<_>>();
+    /// let mut tokens = StreamTokens::new(data.iter().copied());
+    /// tokens.enable_rolling_hash(16);
+    /// let mut total = 0;
+    /// loop {
+    ///     let chunk = tokens.take_until_chunk_boundary(0x3f, 32, 256);
+    ///     if chunk.is_empty() {
+    ///         break;
+    ///     }
+    ///     assert!(chunk.len() <= 256);
+    ///     total += chunk.len();
+    /// }
+    /// assert_eq!(total, 2000);
+    /// ```
+    pub fn take_until_chunk_boundary(&mut self, mask: u64, min: usize, max: usize) -> Vec<u8> {
+        let mut chunk = Vec::new();
+        while chunk.len() < max {
+            let Some(byte) = self.next() else {
+                break;
+            };
+            chunk.push(byte);
+            if chunk.len() >= min && self.current_hash() & mask == 0 {
+                break;
+            }
+        }
+        chunk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hash of `values` computed from scratch.
+    fn reference(values: &[u8]) -> u64 {
+        values.iter().fold(0, |hash: u64, &v| {
+            hash.wrapping_mul(BASE).wrapping_add(v.into())
+        })
+    }
+
+    #[test]
+    fn matches_reference_over_window() {
+        let data = b"the quick brown fox jumps over the lazy dog";
+        let mut tokens = StreamTokens::new(data.iter().copied());
+        tokens.enable_rolling_hash(5);
+        assert_eq!(tokens.current_hash(), 0);
+        for consumed in 1..=data.len() {
+            tokens.next();
+            let window = &data[consumed.saturating_sub(5)..consumed];
+            assert_eq!(tokens.current_hash(), reference(window));
+        }
+    }
+
+    #[test]
+    fn rolls_in_items_read_again() {
+        let mut tokens = StreamTokens::new(b"abcd".iter().copied());
+        tokens.enable_rolling_hash(2);
+        tokens.next();
+        let loc = tokens.location();
+        tokens.take(2).consume();
+        assert_eq!(tokens.current_hash(), reference(b"bc"));
+        tokens.set_location(loc);
+        tokens.next();
+        assert_eq!(tokens.current_hash(), reference(b"cb"));
+    }
+
+    #[test]
+    fn disabled_or_empty_window() {
+        let mut tokens = StreamTokens::new(b"abc".iter().copied());
+        tokens.next();
+        assert_eq!(tokens.current_hash(), 0);
+        tokens.enable_rolling_hash(0);
+        tokens.next();
+        assert_eq!(tokens.current_hash(), 0);
+    }
+
+    #[test]
+    fn chunk_boundaries_follow_hash() {
+        // Deterministic pseudo-random bytes.
+        let data = (0..4000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect::<Vec<_>>();
+        let (mask, min, max, window) = (0x1f, 8, 100, 4);
+
+        // Boundaries found by hashing each window from scratch.
+        let mut expected = Vec::new();
+        let mut start = 0;
+        for end in 1..=data.len() {
+            let len = end - start;
+            let hash = reference(&data[end.saturating_sub(window)..end]);
+            if len == max || (len >= min && hash & mask == 0) {
+                expected.push(end);
+                start = end;
+            }
+        }
+        if start < data.len() {
+            expected.push(data.len());
+        }
+        assert!(expected.len() > 10);
+
+        let mut tokens = StreamTokens::new(data.iter().copied());
+        tokens.enable_rolling_hash(window);
+        let mut boundaries = Vec::new();
+        let mut offset = 0;
+        loop {
+            let chunk = tokens.take_until_chunk_boundary(mask, min, max);
+            if chunk.is_empty() {
+                break;
+            }
+            assert_eq!(chunk, data[offset..offset + chunk.len()]);
+            offset += chunk.len();
+            boundaries.push(offset);
+        }
+        assert_eq!(boundaries, expected);
+    }
+
+    #[test]
+    fn chunk_min_and_max() {
+        let data = [7u8; 50];
+        // Every hash matches an empty mask, so chunks are as short as allowed.
+        let mut tokens = StreamTokens::new(data.iter().copied());
+        tokens.enable_rolling_hash(4);
+        assert_eq!(tokens.take_until_chunk_boundary(0, 10, 20).len(), 10);
+
+        // No hash matches a full mask, so chunks are as long as allowed.
+        let mut tokens = StreamTokens::new(data.iter().copied());
+        tokens.enable_rolling_hash(4);
+        assert_eq!(tokens.take_until_chunk_boundary(u64::MAX, 10, 20).len(), 20);
+        assert_eq!(tokens.take_until_chunk_boundary(u64::MAX, 10, 20).len(), 20);
+        assert_eq!(tokens.take_until_chunk_boundary(u64::MAX, 10, 20).len(), 10);
+        assert!(tokens
+            .take_until_chunk_boundary(u64::MAX, 10, 20)
+            .is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StreamTokens::expect_sequence`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SequenceMismatch<Item> {
+    /// Position in the sequence of the first token that didn't match.
+    pub index: usize,
+    /// The token the sequence has there.
+    pub expected: Item,
+    /// The token the stream has there, or [`None`] if it ended first.
+    pub found: Option<Item>,
+}
+
+impl<Item: fmt::Debug> fmt::Display for SequenceMismatch<Item> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.found {
+            Some(found) => write!(
+                f,
+                "expected {:?} at index {} but found {:?}",
+                self.expected, self.index, found
+            ),
+            None => write!(
+                f,
+                "expected {:?} at index {} but the stream ended",
+                self.expected, self.index
+            ),
+        }
+    }
+}
+
+impl<Item: fmt::Debug> core::error::Error for SequenceMismatch<Item> {}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone + PartialEq,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Like [`Tokens::tokens()`], but on a mismatch reports where and how the stream diverged from `seq`.
+    /// On error the location is reset to where matching began.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{SequenceMismatch, StreamTokens, Tokens};
+    ///
+    /// let mut tokens = StreamTokens::new("HTTP/1.0".chars());
+    /// let err = tokens.expect_sequence("HTTP/1.1".chars()).unwrap_err();
+    /// assert_eq!(
+    ///     err,
+    ///     SequenceMismatch { index: 7, expected: '1', found: Some('0') }
+    /// );
+    /// assert!(tokens.expect_sequence("HTTP/".chars()).is_ok());
+    /// ```
+    pub fn expect_sequence<S>(&mut self, seq: S) -> Result<(), SequenceMismatch<I::Item>>
+    where
+        S: IntoIterator<Item = I::Item>,
+    {
+        let from = self.location();
+        for (index, expected) in seq.into_iter().enumerate() {
+            let found = self.next();
+            if found.as_ref() != Some(&expected) {
+                self.set_location(from);
+                return Err(SequenceMismatch {
+                    index,
+                    expected,
+                    found,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::String, vec::Vec};
+
+    #[test]
+    fn expect_sequence_full_match() {
+        let mut tokens = StreamTokens::new(b"\x7fELF\x02".iter().copied());
+        assert_eq!(tokens.expect_sequence(*b"\x7fELF"), Ok(()));
+        assert_eq!(tokens.expect_sequence([]), Ok(()));
+        assert_eq!(tokens.as_iter().collect::<Vec<_>>(), [2]);
+    }
+
+    #[test]
+    fn expect_sequence_mismatch_rewinds() {
+        let mut tokens = StreamTokens::new("GET /".chars());
+        assert_eq!(
+            tokens.expect_sequence("GEX".chars()),
+            Err(SequenceMismatch {
+                index: 2,
+                expected: 'X',
+                found: Some('T'),
+            })
+        );
+        assert_eq!(tokens.collect::<String>(), "GET /");
+    }
+
+    #[test]
+    fn expect_sequence_eof() {
+        let mut tokens = StreamTokens::new("GE".chars());
+        let err = tokens.expect_sequence("GET".chars()).unwrap_err();
+        assert_eq!(
+            err,
+            SequenceMismatch {
+                index: 2,
+                expected: 'T',
+                found: None,
+            }
+        );
+        assert_eq!(
+            alloc::format!("{err}"),
+            "expected 'T' at index 2 but the stream ended"
+        );
+        assert_eq!(tokens.collect::<String>(), "GE");
+    }
+}
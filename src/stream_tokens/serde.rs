@@ -0,0 +1,75 @@
+use super::{StreamTokens, StreamTokensBuffer, StreamTokensLocation};
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The offset of a [`StreamTokensLocation`] without its connection to a [`StreamTokens`], so it can be persisted.
+///
+/// Turn it back into a location with [`StreamTokens::location_from_serializable`].
+/// That is only meaningful for a [`StreamTokens`] over the same content as the one the location came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SerializableLocation(pub usize);
+
+impl From<&StreamTokensLocation> for SerializableLocation {
+    fn from(location: &StreamTokensLocation) -> Self {
+        Self(location.cursor)
+    }
+}
+
+impl Serialize for SerializableLocation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerializableLocation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        usize::deserialize(deserializer).map(Self)
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Check out a live location at `location`'s offset,
+    /// or [`None`] if the items after that offset are no longer (or not yet) buffered.
+    pub fn location_from_serializable(
+        &self,
+        location: SerializableLocation,
+    ) -> Option<StreamTokensLocation> {
+        let buffered =
+            self.buffer.oldest_elem_cursor..=self.buffer.oldest_elem_cursor + self.buffer.len;
+        buffered
+            .contains(&location.0)
+            .then(|| self.checkout_at(location.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use yap::Tokens;
+
+    #[test]
+    fn serializable_location_round_trip() {
+        let mut tokens = StreamTokens::new("abcdef".chars());
+        let _start = tokens.location();
+        tokens.take(2).consume();
+
+        let saved = serde_json::to_string(&SerializableLocation::from(&tokens.location())).unwrap();
+        assert_eq!(saved, "2");
+        tokens.take(2).consume();
+
+        let restored = serde_json::from_str::<SerializableLocation>(&saved).unwrap();
+        let loc = tokens.location_from_serializable(restored).unwrap();
+        tokens.set_location(loc);
+        assert_eq!(tokens.collect::<String>(), "cdef");
+
+        // Past the end of what has been read.
+        assert!(tokens
+            .location_from_serializable(SerializableLocation(7))
+            .is_none());
+    }
+}
@@ -1,8 +1,19 @@
-use super::StreamTokensBuffer;
+use super::{StreamTokensBuffer, StreamTokensLocation};
 use crate::StreamTokens;
-use alloc::string::String;
+use alloc::{string::String, vec, vec::Vec};
+use core::str::Chars;
 use yap::Tokens;
 
+pub(crate) mod base64;
+pub(crate) mod class;
+pub(crate) mod csv;
+pub(crate) mod fixed_width;
+pub(crate) mod hex;
+pub(crate) mod int;
+pub(crate) mod ip;
+pub(crate) mod quoted;
+pub(crate) mod trivia;
+
 /// [`StrStreamTokens`] is like [`StreamTokens`] but optimized for more efficient usage of [`Tokens::parse()`] and related methods when wrapping `Iterator<Item = char>`.
 ///
 /// See [`Self::new`] for example usage.
@@ -12,12 +23,12 @@ pub struct StrStreamTokens<
     Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
 >(StreamTokens<I, Buffer>);
 
+/// Items are chars, not bytes, so [`StreamTokensBuffer::len`] and [`StreamTokensBuffer::drain_front`] are `O(len)`.
 impl StreamTokensBuffer<char> for String {
     fn drain_front(&mut self, n: usize) {
-        if n >= self.len() {
-            self.clear();
-        } else {
-            self.drain(..n).for_each(drop);
+        match self.char_indices().nth(n) {
+            Some((byte_idx, _)) => self.drain(..byte_idx).for_each(drop),
+            None => self.clear(),
         }
     }
 
@@ -28,6 +39,23 @@ impl StreamTokensBuffer<char> for String {
     fn get(&self, idx: usize) -> Option<char> {
         self.chars().nth(idx)
     }
+
+    fn len(&self) -> usize {
+        self.chars().count()
+    }
+
+    fn push_many(&mut self, items: &[char]) {
+        self.extend(items);
+    }
+
+    /// Reserves `additional` bytes, which is enough for ASCII.
+    fn reserve(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+
+    fn shrink_to(&mut self, min_capacity: usize) {
+        self.shrink_to(min_capacity);
+    }
 }
 
 impl<I> StrStreamTokens<I, String>
@@ -61,6 +89,420 @@ where
     }
 }
 
+/// Allows `"abc".into()` in place of `StrStreamTokens::new("abc".chars())`.
+///
+/// # Example
+///
+/// ```rust
+/// use yap_streaming::{Tokens, StrStreamTokens};
+///
+/// let mut tokens: StrStreamTokens<_, _> = "123abc".into();
+/// assert_eq!(tokens.take_while(|c| c.is_numeric()).parse::<u16, String>(), Ok(123));
+/// assert_eq!(tokens.collect::<String>(), "abc");
+/// ```
+impl<'a> From<&'a str> for StrStreamTokens<Chars<'a>, String> {
+    fn from(value: &'a str) -> Self {
+        Self::new(value.chars())
+    }
+}
+
+/// Like the `From<&str>` impl but owning the chars.
+impl From<String> for StrStreamTokens<vec::IntoIter<char>, String> {
+    fn from(value: String) -> Self {
+        Self::new(value.chars().collect::<Vec<_>>().into_iter())
+    }
+}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator<Item = char>,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// Parse the longest prefix of the stream that looks like an [`f64`], leaving the cursor just after it.
+    ///
+    /// The prefix is an optional sign, digits with at most one `.`, and an optional exponent made of `e` or `E`,
+    /// an optional sign, and digits. Only the float itself is buffered while parsing.
+    /// An exponent marker without digits (like `"1e"`) is rejected.
+    /// On error the location is reset to where parsing began.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("-1.5e3.2".chars());
+    /// assert_eq!(tokens.parse_f64(), Ok(-1500.0));
+    /// assert_eq!(tokens.collect::<String>(), ".2");
+    /// ```
+    pub fn parse_f64(&mut self) -> Result<f64, core::num::ParseFloatError> {
+        let from = self.location();
+
+        self.optional(|t| t.next().filter(|c| matches!(c, '+' | '-')));
+        let mut digits = self.skip_while(char::is_ascii_digit);
+        if self.optional(|t| t.next().filter(|&c| c == '.')).is_some() {
+            digits += self.skip_while(char::is_ascii_digit);
+        }
+        // Only consider an exponent if there is a mantissa for it to belong to.
+        if digits > 0
+            && self
+                .optional(|t| t.next().filter(|c| matches!(c, 'e' | 'E')))
+                .is_some()
+        {
+            self.optional(|t| t.next().filter(|c| matches!(c, '+' | '-')));
+            self.skip_while(char::is_ascii_digit);
+        }
+
+        let res = self.buffered_str(from.cursor, self.0.cursor).parse();
+
+        // Reset location on error.
+        if res.is_err() {
+            self.set_location(from);
+        }
+        res
+    }
+
+    /// Consume `kw` if the stream continues with it.
+    /// Otherwise the location is left unchanged, even if part of `kw` matched.
+    pub fn parse_keyword(&mut self, kw: &str) -> bool {
+        let from = self.location();
+        if kw.chars().all(|c| self.next() == Some(c)) {
+            true
+        } else {
+            self.set_location(from);
+            false
+        }
+    }
+
+    /// Number of bytes the UTF-8 encoding of the chars from `from` up to `to` takes, without collecting them.
+    /// Returns [`None`] if any of those chars are no longer buffered or `to` is before `from`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("añb".chars());
+    /// let from = tokens.location();
+    /// tokens.take(2).consume();
+    /// let to = tokens.location();
+    /// assert_eq!(tokens.span_byte_len(&from, &to), Some(3));
+    /// ```
+    pub fn span_byte_len(
+        &self,
+        from: &StreamTokensLocation,
+        to: &StreamTokensLocation,
+    ) -> Option<usize> {
+        let start = from.cursor.checked_sub(self.0.buffer.oldest_elem_cursor)?;
+        let len = to.cursor.checked_sub(from.cursor)?;
+        let (chars, bytes) = self
+            .0
+            .buffer
+            .elements
+            .chars()
+            .skip(start)
+            .take(len)
+            .fold((0, 0), |(chars, bytes), c| {
+                (chars + 1, bytes + c.len_utf8())
+            });
+        (chars == len).then_some(bytes)
+    }
+
+    /// Up to `radius` buffered chars either side of `at` for an error message, with the char at `at` in brackets.
+    /// `...` marks where the stream continues past the snippet.
+    /// Returns [`None`] if `at` itself is no longer buffered.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("let x = 1 + ;".chars());
+    /// let _start = tokens.location();
+    /// tokens.skip_while(|&c| c != ';');
+    /// let at = tokens.location();
+    /// assert_eq!(tokens.error_context(&at, 4).as_deref(), Some("...1 + [;]"));
+    /// ```
+    pub fn error_context(&self, at: &StreamTokensLocation, radius: usize) -> Option<String> {
+        let idx = at.cursor.checked_sub(self.0.buffer.oldest_elem_cursor)?;
+        if idx > self.0.buffer.len {
+            return None;
+        }
+        let start = idx.saturating_sub(radius);
+        let mut chars = self.0.buffer.elements.chars().skip(start);
+
+        let mut out = String::new();
+        if self.0.buffer.oldest_elem_cursor + start > 0 {
+            out.push_str("...");
+        }
+        out.extend(chars.by_ref().take(idx - start));
+        out.push('[');
+        out.extend(chars.next());
+        out.push(']');
+        out.extend(chars.by_ref().take(radius));
+        if chars.next().is_some() {
+            out.push_str("...");
+        }
+        Some(out)
+    }
+
+    /// Like parsing [`Tokens::take_while`] but also returns the chars that were parsed, borrowed from the buffer.
+    /// On error the location is reset.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("0042 rest".chars());
+    /// assert_eq!(tokens.parse_with_str(char::is_ascii_digit), Ok((42, "0042")));
+    /// ```
+    pub fn parse_with_str<Out, F>(&mut self, take_while: F) -> Result<(Out, &str), Out::Err>
+    where
+        Out: core::str::FromStr,
+        F: FnMut(&char) -> bool,
+    {
+        let from = self.location();
+        self.take_while(take_while).consume();
+        match self.buffered_str(from.cursor, self.0.cursor).parse() {
+            Ok(out) => Ok((out, self.buffered_str(from.cursor, self.0.cursor))),
+            Err(e) => {
+                // Reset location on error.
+                self.set_location(from);
+                Err(e)
+            }
+        }
+    }
+
+    /// Parse `key` `kv_sep` `value` pairs separated by `pair_sep`, such as a query string.
+    ///
+    /// `key` and `val` are given the text of each key and value, which may be empty.
+    /// Parsing stops before the first pair without `kv_sep` or that `key` or `val` rejects.
+    /// A trailing `pair_sep` is consumed. Only the pair being parsed is buffered.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("a=1&b=2".chars());
+    /// let pairs = tokens.parse_pairs(|k| k.chars().next(), '=', '&', |v| v.parse::<u8>().ok());
+    /// assert_eq!(pairs, [('a', 1), ('b', 2)]);
+    /// ```
+    pub fn parse_pairs<K, V, FK, FV>(
+        &mut self,
+        mut key: FK,
+        kv_sep: char,
+        pair_sep: char,
+        mut val: FV,
+    ) -> Vec<(K, V)>
+    where
+        FK: FnMut(&str) -> Option<K>,
+        FV: FnMut(&str) -> Option<V>,
+    {
+        let mut pairs = Vec::new();
+        loop {
+            let from = self.location();
+            self.skip_while(|&c| c != kv_sep && c != pair_sep);
+            let pair = match key(self.buffered_str(from.cursor, self.0.cursor)) {
+                Some(k) if self.token(kv_sep) => {
+                    let val_from = self.0.cursor;
+                    self.skip_while(|&c| c != pair_sep);
+                    val(self.buffered_str(val_from, self.0.cursor)).map(|v| (k, v))
+                }
+                _ => None,
+            };
+            match pair {
+                Some(pair) => pairs.push(pair),
+                None => {
+                    self.set_location(from);
+                    break;
+                }
+            }
+            if !self.token(pair_sep) {
+                break;
+            }
+        }
+        pairs
+    }
+
+    /// Run `f`, also returning the chars it consumed, borrowed from the buffer.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("foo_1(".chars());
+    /// let (_, ident) = tokens.recognize(|t| t.skip_while(|c| c.is_alphanumeric() || *c == '_'));
+    /// assert_eq!(ident, "foo_1");
+    /// ```
+    pub fn recognize<T, F>(&mut self, f: F) -> (T, &str)
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let from = self.location();
+        let out = f(self);
+        (out, self.buffered_str(from.cursor, self.0.cursor))
+    }
+
+    /// Consume chars while `pred` matches and return the id `table` has for them,
+    /// adding the next unused id if they aren't in it yet.
+    ///
+    /// The chars are looked up in place in the buffer, so a [`String`] is only allocated for new entries.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut table = HashMap::new();
+    /// let mut tokens = StrStreamTokens::new("x y x".chars());
+    /// let mut ids = Vec::new();
+    /// loop {
+    ///     ids.push(tokens.parse_interned(&mut table, char::is_ascii_alphabetic));
+    ///     if !tokens.token(' ') {
+    ///         break;
+    ///     }
+    /// }
+    /// assert_eq!(ids, [0, 1, 0]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn parse_interned<F>(
+        &mut self,
+        table: &mut std::collections::HashMap<String, u32>,
+        pred: F,
+    ) -> u32
+    where
+        F: FnMut(&char) -> bool,
+    {
+        let (_, key) = self.recognize(|t| t.skip_while(pred));
+        if let Some(&id) = table.get(key) {
+            return id;
+        }
+        let id = u32::try_from(table.len()).expect("fewer than u32::MAX interned strings");
+        table.insert(key.into(), id);
+        id
+    }
+
+    /// Borrow up to the next `n` chars without consuming them.
+    /// Fewer are returned if the stream ends first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("<!-- x".chars());
+    /// if tokens.peek_str(4) == "<!--" {
+    ///     tokens.take(4).consume();
+    /// }
+    /// assert_eq!(tokens.peek_str(4), " x");
+    /// ```
+    pub fn peek_str(&mut self, n: usize) -> &str {
+        let available = self.0.fill(n).min(n);
+        self.buffered_str(self.0.cursor, self.0.cursor + available)
+    }
+
+    /// Consume the longest keyword in `table` that the stream continues with, and return its value.
+    /// Otherwise the location is left unchanged.
+    ///
+    /// Only as many chars as the longest keyword are buffered to compare against.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let table = [(">", 'g'), (">=", 'G'), ("=", 'e')];
+    /// let mut tokens = StrStreamTokens::new(">=>!".chars());
+    /// assert_eq!(tokens.parse_enum(&table), Some('G'));
+    /// assert_eq!(tokens.parse_enum(&table), Some('g'));
+    /// assert_eq!(tokens.parse_enum(&table), None);
+    /// assert_eq!(tokens.collect::<String>(), "!");
+    /// ```
+    pub fn parse_enum<T: Clone>(&mut self, table: &[(&str, T)]) -> Option<T> {
+        let longest = table.iter().map(|(kw, _)| kw.chars().count()).max()?;
+        let ahead = self.peek_str(longest);
+        let (kw, value) = table
+            .iter()
+            .filter(|(kw, _)| ahead.starts_with(kw))
+            .max_by_key(|(kw, _)| kw.len())?;
+        let value = value.clone();
+        self.take(kw.chars().count()).consume();
+        Some(value)
+    }
+
+    /// Like [`StreamTokens::count_newlines_since`], but counting in the buffered `str`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("a\nb\nc".chars());
+    /// let start = tokens.location();
+    /// tokens.take(4).consume();
+    /// assert_eq!(tokens.count_newlines_since(&start), Some(2));
+    /// ```
+    pub fn count_newlines_since(&self, from: &StreamTokensLocation) -> Option<usize> {
+        if !from.is_valid(&self.0) || from.cursor > self.0.cursor {
+            return None;
+        }
+        Some(
+            self.buffered_str(from.cursor, self.0.cursor)
+                .matches('\n')
+                .count(),
+        )
+    }
+
+    /// Consume `true` or `false` with [`Self::parse_keyword`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("false,tru".chars());
+    /// assert_eq!(tokens.parse_bool(), Some(false));
+    /// assert!(tokens.token(','));
+    /// assert_eq!(tokens.parse_bool(), None);
+    /// ```
+    pub fn parse_bool(&mut self) -> Option<bool> {
+        if self.parse_keyword("true") {
+            Some(true)
+        } else if self.parse_keyword("false") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// The buffered chars from cursor `from` up to cursor `to`.
+    fn buffered_str(&self, from: usize, to: usize) -> &str {
+        let buffered: &str = &self.0.buffer.elements;
+        let from = from - self.0.buffer.oldest_elem_cursor;
+        let to = to - self.0.buffer.oldest_elem_cursor;
+        // Char and byte indexes are the same unless something buffered is multibyte.
+        if buffered.len() == self.0.buffer.len {
+            return &buffered[from..to];
+        }
+        let byte_idx = |n| {
+            buffered
+                .char_indices()
+                .nth(n)
+                .map_or(buffered.len(), |(i, _)| i)
+        };
+        &buffered[byte_idx(from)..byte_idx(to)]
+    }
+}
+
 impl<I, Buffer> Tokens for StrStreamTokens<I, Buffer>
 where
     I: Iterator,
@@ -96,7 +538,7 @@ where
         let from = self.location();
         while self.0.next().is_some() {}
         // Parse everything.
-        let res = self.0.buffer.elements[from.cursor - self.0.buffer.oldest_elem_cursor..].parse();
+        let res = self.buffered_str(from.cursor, self.0.cursor).parse();
         // Reset location on error.
         if res.is_err() {
             self.set_location(from);
@@ -112,9 +554,7 @@ where
         Out: core::str::FromStr,
         Buf: FromIterator<Self::Item> + core::ops::Deref<Target = str>,
     {
-        self.0.buffer.elements[from.cursor - self.0.buffer.oldest_elem_cursor
-            ..to.cursor - self.0.buffer.oldest_elem_cursor]
-            .parse()
+        self.buffered_str(from.cursor, to.cursor).parse()
     }
     fn parse_take<Out, Buf>(&mut self, n: usize) -> Result<Out, <Out as core::str::FromStr>::Err>
     where
@@ -125,9 +565,7 @@ where
         let from = self.location();
         self.take(n).consume();
 
-        let res = self.0.buffer.elements[from.cursor - self.0.buffer.oldest_elem_cursor
-            ..self.0.cursor - self.0.buffer.oldest_elem_cursor]
-            .parse();
+        let res = self.buffered_str(from.cursor, self.0.cursor).parse();
 
         // Reset location on error.
         if res.is_err() {
@@ -148,9 +586,7 @@ where
         let from = self.location();
         self.take_while(take_while).consume();
 
-        let res = self.0.buffer.elements[from.cursor - self.0.buffer.oldest_elem_cursor
-            ..self.0.cursor - self.0.buffer.oldest_elem_cursor]
-            .parse();
+        let res = self.buffered_str(from.cursor, self.0.cursor).parse();
 
         // Reset location on error.
         if res.is_err() {
@@ -243,4 +679,287 @@ mod tests {
         assert_eq!(n, 12);
         assert_eq!(tokens.collect::<String>(), "3ab+=");
     }
+
+    #[test]
+    fn from_owned_string() {
+        let mut tokens: StrStreamTokens<_, _> = String::from("1.5x").into();
+        assert_eq!(tokens.parse_f64(), Ok(1.5));
+        assert_eq!(tokens.collect::<String>(), "x");
+    }
+
+    #[test]
+    fn parse_keyword_rewinds_fully() {
+        let mut tokens = StrStreamTokens::new("null nul nulL".chars());
+
+        assert!(tokens.parse_keyword("null"));
+        assert!(tokens.token(' '));
+
+        // Partial match then end of keyword mismatch.
+        assert!(!tokens.parse_keyword("null"));
+        assert!(tokens.parse_keyword("nul "));
+
+        // Partial match then divergence.
+        assert!(!tokens.parse_keyword("null"));
+        // Keyword longer than the remaining input.
+        assert!(!tokens.parse_keyword("nulL and more"));
+        assert_eq!(tokens.collect::<String>(), "nulL");
+    }
+
+    #[test]
+    fn parse_enum_longest_match() {
+        #[derive(Clone, Debug, PartialEq)]
+        enum Op {
+            A,
+            B,
+        }
+        let table = [(">", Op::A), (">=", Op::B)];
+
+        let mut tokens = StrStreamTokens::new(">=1".chars());
+        assert_eq!(tokens.parse_enum(&table), Some(Op::B));
+        assert_eq!(tokens.collect::<String>(), "1");
+
+        let mut tokens = StrStreamTokens::new(">1".chars());
+        assert_eq!(tokens.parse_enum(&table), Some(Op::A));
+        assert_eq!(tokens.collect::<String>(), "1");
+
+        // At the end of the stream only keywords that fit can match.
+        let mut tokens = StrStreamTokens::new(">".chars());
+        assert_eq!(tokens.parse_enum(&table), Some(Op::A));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn parse_enum_no_match() {
+        let mut tokens = StrStreamTokens::new("<=".chars());
+        assert_eq!(tokens.parse_enum(&[(">", 0), (">=", 1)]), None);
+        assert_eq!(tokens.parse_enum::<u8>(&[]), None);
+        assert_eq!(tokens.collect::<String>(), "<=");
+
+        // Keywords are compared by chars, not bytes.
+        let mut tokens = StrStreamTokens::new("→→x".chars());
+        assert_eq!(tokens.parse_enum(&[("→", 1), ("→→", 2)]), Some(2));
+        assert_eq!(tokens.collect::<String>(), "x");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn parse_interned_repeated_identifiers() {
+        let mut table = std::collections::HashMap::new();
+        let mut tokens = StrStreamTokens::new("foo bar foo baz bar foo".chars());
+        let mut ids = Vec::new();
+        while !tokens.0.at_eof() {
+            ids.push(tokens.parse_interned(&mut table, |c| c.is_alphanumeric()));
+            tokens.skip_while(|c| c.is_whitespace());
+        }
+        assert_eq!(ids, [0, 1, 0, 2, 1, 0]);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table["baz"], 2);
+    }
+
+    #[test]
+    fn parse_bool_values() {
+        let mut tokens = StrStreamTokens::new("truefalsefals".chars());
+        assert_eq!(tokens.parse_bool(), Some(true));
+        assert_eq!(tokens.parse_bool(), Some(false));
+        assert_eq!(tokens.parse_bool(), None);
+        assert_eq!(tokens.collect::<String>(), "fals");
+    }
+
+    #[test]
+    fn parse_pairs_query_string() {
+        let to_owned = |s: &str| Some(String::from(s));
+        let mut tokens = StrStreamTokens::new("name=yap&lang=rust".chars());
+        assert_eq!(
+            tokens.parse_pairs(to_owned, '=', '&', to_owned),
+            [
+                ("name".into(), "yap".into()),
+                ("lang".into(), "rust".into())
+            ]
+        );
+        assert_eq!(tokens.next(), None);
+
+        // Trailing separator.
+        let mut tokens = StrStreamTokens::new("a=1;".chars());
+        assert_eq!(
+            tokens.parse_pairs(to_owned, '=', ';', to_owned),
+            [("a".into(), "1".into())]
+        );
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn parse_pairs_empty_value_and_stop() {
+        let to_owned = |s: &str| Some(String::from(s));
+        let mut tokens = StrStreamTokens::new("a=&b=2&c&d=4".chars());
+        assert_eq!(
+            tokens.parse_pairs(to_owned, '=', '&', to_owned),
+            [("a".into(), "".into()), ("b".into(), "2".into())]
+        );
+        // Stopped before the pair without a value.
+        assert_eq!(tokens.collect::<String>(), "c&d=4");
+
+        // Only one pair is buffered at a time.
+        let input = "key=value&".repeat(100);
+        let mut tokens = StrStreamTokens::new(input.chars());
+        assert_eq!(tokens.parse_pairs(to_owned, '=', '&', to_owned).len(), 100);
+        assert!(tokens.0.peak_buffer_len() <= "key=value&".len());
+    }
+
+    #[test]
+    fn recognize_as_str() {
+        let mut tokens = StrStreamTokens::new("ünïcode ident".chars());
+        let (n, ident) = tokens.recognize(|t| t.skip_while(|c| c.is_alphabetic()));
+        assert_eq!(n, 7);
+        assert_eq!(ident, "ünïcode");
+        assert_eq!(tokens.collect::<String>(), " ident");
+    }
+
+    #[test]
+    fn peek_str_does_not_consume() {
+        let mut tokens = StrStreamTokens::new("añb✓c".chars());
+        assert_eq!(tokens.peek_str(0), "");
+        assert_eq!(tokens.peek_str(3), "añb");
+        assert_eq!(tokens.take(3).collect::<String>(), "añb");
+
+        assert_eq!(tokens.peek_str(3), "✓c");
+        assert_eq!(tokens.collect::<String>(), "✓c");
+        assert_eq!(tokens.peek_str(3), "");
+    }
+
+    #[test]
+    fn parse_with_str_returns_source() {
+        let mut tokens = StrStreamTokens::new("ñ-17;".chars());
+        assert!(tokens.token('ñ'));
+
+        let (n, src) = tokens
+            .parse_with_str::<i32, _>(|&c| c == '-' || c.is_ascii_digit())
+            .unwrap();
+        assert_eq!(n, -17);
+        assert_eq!(src, "-17");
+
+        assert!(tokens.parse_with_str::<i32, _>(|&c| c != '!').is_err());
+        assert_eq!(tokens.collect::<String>(), ";");
+    }
+
+    #[test]
+    fn parse_after_multibyte() {
+        let mut tokens = StrStreamTokens::new("é12 ü-3.5 ✓7".chars());
+
+        let start = tokens.location();
+        assert!(tokens.token('é'));
+        let n = tokens
+            .take_while(char::is_ascii_digit)
+            .parse::<u8, String>();
+        assert_eq!(n, Ok(12));
+        assert!(tokens.tokens(" ü".chars()));
+        assert_eq!(tokens.parse_f64(), Ok(-3.5));
+
+        // Drain the multibyte chars before parsing again.
+        drop(start);
+        assert!(tokens.tokens(" ✓".chars()));
+        let _loc = tokens.location();
+        assert_eq!(tokens.take(1).parse::<u8, String>(), Ok(7));
+        assert_eq!(
+            tokens.0.buffer.len,
+            StreamTokensBuffer::len(&tokens.0.buffer.elements)
+        );
+    }
+
+    #[test]
+    fn error_context_snippet() {
+        let mut tokens = StrStreamTokens::new("xyfooBarbaz".chars());
+        let start = tokens.location();
+        tokens.take(5).consume();
+        let at = tokens.location();
+        tokens.consume();
+
+        assert_eq!(
+            tokens.error_context(&at, 3).as_deref(),
+            Some("...foo[B]arb...")
+        );
+        assert_eq!(tokens.error_context(&start, 1).as_deref(), Some("[x]y..."));
+        assert_eq!(
+            tokens.error_context(&tokens.location(), 2).as_deref(),
+            Some("...az[]")
+        );
+
+        // Not buffered once a bounded buffer drops it.
+        let mut tokens = StrStreamTokens::new("abcdef".chars());
+        tokens.0.buffer.max_len = Some(3);
+        let start = tokens.location();
+        tokens.take(3).consume();
+        let at = tokens.location();
+        tokens.take(2).consume();
+        assert_eq!(tokens.error_context(&start, 2), None);
+        assert_eq!(tokens.error_context(&at, 2).as_deref(), Some("...c[d]e"));
+    }
+
+    #[test]
+    fn span_byte_len_multibyte() {
+        let mut tokens = StrStreamTokens::new("héllo wörld ✓".chars());
+
+        let start = tokens.location();
+        tokens.take(5).consume();
+        let hello = tokens.location();
+        tokens.take(8).consume();
+        let end = tokens.location();
+
+        assert_eq!(tokens.span_byte_len(&start, &hello), Some(6));
+        assert_eq!(tokens.span_byte_len(&hello, &end), Some(11));
+        assert_eq!(
+            tokens.span_byte_len(&start, &end),
+            Some("héllo wörld ✓".len())
+        );
+        assert_eq!(tokens.span_byte_len(&end, &start), None);
+    }
+
+    #[test]
+    fn span_byte_len_drained() {
+        let mut tokens = StrStreamTokens::new("abcdef".chars());
+        tokens.0.buffer.max_len = Some(3);
+
+        let from = tokens.location();
+        tokens.take(2).consume();
+        let to = tokens.location();
+        assert_eq!(tokens.span_byte_len(&from, &to), Some(2));
+
+        // Bounded buffer drops "a".
+        tokens.take(2).consume();
+        assert_eq!(tokens.span_byte_len(&from, &to), None);
+        assert_eq!(tokens.span_byte_len(&to, &tokens.location()), Some(2));
+    }
+
+    #[test]
+    fn parse_f64_edge_cases() {
+        let mut tokens = StrStreamTokens::new("42,".chars());
+        assert_eq!(tokens.parse_f64(), Ok(42.0));
+        assert_eq!(tokens.collect::<String>(), ",");
+
+        let mut tokens = StrStreamTokens::new("+6.02E23 mol".chars());
+        assert_eq!(tokens.parse_f64(), Ok(6.02e23));
+        assert_eq!(tokens.collect::<String>(), " mol");
+
+        let mut tokens = StrStreamTokens::new("2.5e-3".chars());
+        assert_eq!(tokens.parse_f64(), Ok(2.5e-3));
+        assert_eq!(tokens.next(), None);
+
+        // Second dot is not part of the float.
+        let mut tokens = StrStreamTokens::new("1.2.3".chars());
+        assert_eq!(tokens.parse_f64(), Ok(1.2));
+        assert_eq!(tokens.collect::<String>(), ".3");
+
+        // Exponent without digits is rejected and rewound.
+        let mut tokens = StrStreamTokens::new("1e".chars());
+        assert!(tokens.parse_f64().is_err());
+        assert_eq!(tokens.collect::<String>(), "1e");
+
+        let mut tokens = StrStreamTokens::new("1e+x".chars());
+        assert!(tokens.parse_f64().is_err());
+        assert_eq!(tokens.collect::<String>(), "1e+x");
+
+        // Nothing float-like.
+        let mut tokens = StrStreamTokens::new("-.e5".chars());
+        assert!(tokens.parse_f64().is_err());
+        assert_eq!(tokens.collect::<String>(), "-.e5");
+    }
 }
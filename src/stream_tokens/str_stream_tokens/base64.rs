@@ -0,0 +1,167 @@
+use super::StrStreamTokens;
+use crate::stream_tokens::StreamTokensBuffer;
+use alloc::vec::Vec;
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StrStreamTokens::decode_base64_into`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base64Error {
+    /// The last group had a single char, which can't encode a whole byte.
+    Truncated,
+    /// `=` came too early in a group, or there weren't enough of them to complete it.
+    InvalidPadding,
+}
+
+impl fmt::Display for Base64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Base64Error::Truncated => write!(f, "base64 group of a single char"),
+            Base64Error::InvalidPadding => write!(f, "invalid base64 padding"),
+        }
+    }
+}
+
+impl core::error::Error for Base64Error {}
+
+/// Value of a char of the standard base64 alphabet.
+fn sextet(c: char) -> Option<u8> {
+    let value = match c {
+        'A'..='Z' => c as u8 - b'A',
+        'a'..='z' => c as u8 - b'a' + 26,
+        '0'..='9' => c as u8 - b'0' + 52,
+        '+' => 62,
+        '/' => 63,
+        _ => return None,
+    };
+    Some(value)
+}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator<Item = char>,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// Decode standard base64 into `out` a group of four chars at a time, returning how many bytes were appended.
+    ///
+    /// Decoding stops before the first char that isn't base64, or after `=` padding.
+    /// Padding is optional, but if present must complete the group.
+    /// Only the group being decoded is buffered, so on error the location is reset to the start of that group
+    /// and `out` keeps the bytes decoded before it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("aGVsbG8=\"".chars());
+    /// let mut out = Vec::new();
+    /// assert_eq!(tokens.decode_base64_into(&mut out), Ok(5));
+    /// assert_eq!(out, b"hello");
+    /// assert_eq!(tokens.next(), Some('"'));
+    /// ```
+    pub fn decode_base64_into(&mut self, out: &mut Vec<u8>) -> Result<usize, Base64Error> {
+        let start_len = out.len();
+        loop {
+            let group = self.location();
+            let mut sextets = [0u8; 4];
+            let mut n = 0;
+            while n < 4 {
+                match self.0.consume_if(|&c| sextet(c).is_some()) {
+                    Some(c) => sextets[n] = sextet(c).unwrap_or_default(),
+                    None => break,
+                }
+                n += 1;
+            }
+            let padded = n < 4 && self.peek() == Some('=');
+            let res = match n {
+                0 if padded => Err(Base64Error::InvalidPadding),
+                0 => return Ok(out.len() - start_len),
+                1 => Err(Base64Error::Truncated),
+                _ if padded && !self.tokens(core::iter::repeat_n('=', 4 - n)) => {
+                    Err(Base64Error::InvalidPadding)
+                }
+                _ => Ok(()),
+            };
+            if let Err(e) = res {
+                self.set_location(group);
+                return Err(e);
+            }
+
+            let [a, b, c, d] = sextets;
+            let bytes = [a << 2 | b >> 4, b << 4 | c >> 2, c << 6 | d];
+            out.extend_from_slice(&bytes[..n - 1]);
+            if n < 4 {
+                return Ok(out.len() - start_len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::String, vec};
+
+    fn decode(input: &str) -> (Result<Vec<u8>, Base64Error>, String) {
+        let mut tokens = StrStreamTokens::new(input.chars());
+        let mut out = Vec::new();
+        let res = tokens.decode_base64_into(&mut out).map(|n| {
+            assert_eq!(n, out.len());
+            out
+        });
+        (res, tokens.collect())
+    }
+
+    #[test]
+    fn decode_base64_padded() {
+        assert_eq!(decode("TWFu"), (Ok(b"Man".to_vec()), "".into()));
+        assert_eq!(decode("TWE=;"), (Ok(b"Ma".to_vec()), ";".into()));
+        assert_eq!(decode("TQ==TQ=="), (Ok(b"M".to_vec()), "TQ==".into()));
+        assert_eq!(decode("+/+/"), (Ok(vec![0xfb, 0xff, 0xbf]), "".into()));
+        // Decoding ends with the padding.
+        assert_eq!(decode("TWE=="), (Ok(b"Ma".to_vec()), "=".into()));
+    }
+
+    #[test]
+    fn decode_base64_unpadded() {
+        assert_eq!(decode("TWFuTWE"), (Ok(b"ManMa".to_vec()), "".into()));
+        assert_eq!(decode("TWFuTQ\n"), (Ok(b"ManM".to_vec()), "\n".into()));
+        assert_eq!(decode(""), (Ok(vec![]), "".into()));
+    }
+
+    #[test]
+    fn decode_base64_stops_at_delimiter() {
+        let mut tokens = StrStreamTokens::new("aGk,eW8=,".chars());
+        let mut out = Vec::new();
+        assert_eq!(tokens.decode_base64_into(&mut out), Ok(2));
+        assert!(tokens.token(','));
+        assert_eq!(tokens.decode_base64_into(&mut out), Ok(2));
+        assert_eq!(out, b"hiyo");
+        assert_eq!(tokens.collect::<String>(), ",");
+    }
+
+    #[test]
+    fn decode_base64_invalid() {
+        let mut tokens = StrStreamTokens::new("TWFuT!".chars());
+        let mut out = Vec::new();
+        assert_eq!(
+            tokens.decode_base64_into(&mut out),
+            Err(Base64Error::Truncated)
+        );
+        // Groups before the bad one are kept.
+        assert_eq!(out, b"Man");
+        assert_eq!(tokens.collect::<String>(), "T!");
+
+        for input in ["=", "TQ=x", "T=="] {
+            let expected = if input == "T==" {
+                Base64Error::Truncated
+            } else {
+                Base64Error::InvalidPadding
+            };
+            let (res, rest) = decode(input);
+            assert_eq!(res, Err(expected), "{input}");
+            assert_eq!(rest, input);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use super::StrStreamTokens;
+use crate::stream_tokens::StreamTokensBuffer;
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+use yap::Tokens;
+
+/// Set of chars made of ranges and individual chars, like `[a-zA-Z0-9_]` in a regex, for [`StrStreamTokens::take_class`].
+///
+/// # Example
+///
+/// ```rust
+/// use yap_streaming::CharClass;
+///
+/// let ident = CharClass::new().range('a'..='z').range('A'..='Z').range('0'..='9').char('_');
+/// assert!(ident.contains('_'));
+/// assert!(!ident.contains('-'));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CharClass {
+    ranges: Vec<RangeInclusive<char>>,
+}
+
+impl CharClass {
+    /// A class without any chars.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the chars in `range`.
+    pub fn range(mut self, range: RangeInclusive<char>) -> Self {
+        self.ranges.push(range);
+        self
+    }
+
+    /// Add `c`.
+    pub fn char(self, c: char) -> Self {
+        self.range(c..=c)
+    }
+
+    /// Whether `c` is in the class.
+    pub fn contains(&self, c: char) -> bool {
+        self.ranges.iter().any(|range| range.contains(&c))
+    }
+}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator<Item = char>,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// Consume chars in `class`, returning them borrowed from the buffer if there are at least `at_least`.
+    /// Otherwise the location is reset and [`None`] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{CharClass, Tokens, StrStreamTokens};
+    ///
+    /// let digits = CharClass::new().range('0'..='9');
+    /// let mut tokens = StrStreamTokens::new("2024-x".chars());
+    /// assert_eq!(tokens.take_class(&digits, 4), Some("2024"));
+    /// assert!(tokens.token('-'));
+    /// assert_eq!(tokens.take_class(&digits, 1), None);
+    /// assert_eq!(tokens.next(), Some('x'));
+    /// ```
+    pub fn take_class(&mut self, class: &CharClass, at_least: usize) -> Option<&str> {
+        let from = self.location();
+        let n = self.take_while(|&c| class.contains(c)).as_iter().count();
+        if n < at_least {
+            self.set_location(from);
+            return None;
+        }
+        Some(self.buffered_str(from.cursor, self.0.cursor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    fn ident() -> CharClass {
+        CharClass::new()
+            .range('a'..='z')
+            .range('A'..='Z')
+            .range('0'..='9')
+            .char('_')
+    }
+
+    #[test]
+    fn take_class_identifiers() {
+        let class = ident();
+        let mut tokens = StrStreamTokens::new("foo_Bar9 x+".chars());
+        assert_eq!(tokens.take_class(&class, 1), Some("foo_Bar9"));
+        assert!(tokens.token(' '));
+        assert_eq!(tokens.take_class(&class, 1), Some("x"));
+        assert_eq!(tokens.take_class(&class, 0), Some(""));
+        assert_eq!(tokens.next(), Some('+'));
+    }
+
+    #[test]
+    fn take_class_multibyte() {
+        let class = CharClass::new().range('α'..='ω');
+        let mut tokens = StrStreamTokens::new("αβγ!".chars());
+        assert_eq!(tokens.take_class(&class, 3), Some("αβγ"));
+        assert_eq!(tokens.next(), Some('!'));
+    }
+
+    #[test]
+    fn take_class_at_least_rewinds() {
+        let mut tokens = StrStreamTokens::new("ab-".chars());
+        assert_eq!(tokens.take_class(&ident(), 3), None);
+        assert_eq!(tokens.collect::<String>(), "ab-");
+
+        let empty = CharClass::new();
+        let mut tokens = StrStreamTokens::new("ab".chars());
+        assert_eq!(tokens.take_class(&empty, 1), None);
+        assert_eq!(tokens.next(), Some('a'));
+    }
+}
@@ -0,0 +1,124 @@
+use super::StrStreamTokens;
+use crate::stream_tokens::StreamTokensBuffer;
+use alloc::string::String;
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StrStreamTokens::parse_csv_field`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvError {
+    /// The stream ended before the closing quote of a quoted field.
+    Unterminated,
+    /// A quoted field's closing quote wasn't followed by the separator, a line ending, or the end of the stream.
+    CharAfterQuote(char),
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Unterminated => write!(f, "missing closing quote"),
+            CsvError::CharAfterQuote(c) => write!(f, "unexpected {c:?} after closing quote"),
+        }
+    }
+}
+
+impl core::error::Error for CsvError {}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator<Item = char>,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// Parse one CSV field, stopping before the following `sep` or line ending.
+    ///
+    /// A field starting with `quote` may contain `sep` and line endings, and a doubled `quote` within it decodes to a single `quote`.
+    /// On error the location is reset to the start of the field.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("1,\"say \"\"hi\"\", ok\"\n".chars());
+    /// assert_eq!(tokens.parse_csv_field(',', '"').as_deref(), Ok("1"));
+    /// assert!(tokens.token(','));
+    /// assert_eq!(tokens.parse_csv_field(',', '"').as_deref(), Ok("say \"hi\", ok"));
+    /// assert!(tokens.token('\n'));
+    /// ```
+    pub fn parse_csv_field(&mut self, sep: char, quote: char) -> Result<String, CsvError> {
+        let from = self.location();
+        let res = self.parse_csv_field_inner(sep, quote);
+        // Reset location on error.
+        if res.is_err() {
+            self.set_location(from);
+        }
+        res
+    }
+
+    fn parse_csv_field_inner(&mut self, sep: char, quote: char) -> Result<String, CsvError> {
+        let is_end = |c: &char| *c == sep || *c == '\n' || *c == '\r';
+        if !self.token(quote) {
+            return Ok(self.take_while(|c| !is_end(c)).collect());
+        }
+        let mut out = String::new();
+        loop {
+            match self.next().ok_or(CsvError::Unterminated)? {
+                c if c == quote && !self.token(quote) => break,
+                c => out.push(c),
+            }
+        }
+        match self.peek() {
+            Some(c) if !is_end(&c) => Err(CsvError::CharAfterQuote(c)),
+            _ => Ok(out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_csv_field_plain() {
+        let mut tokens = StrStreamTokens::new("abc,def\r\n".chars());
+        assert_eq!(tokens.parse_csv_field(',', '"').as_deref(), Ok("abc"));
+        assert!(tokens.token(','));
+        assert_eq!(tokens.parse_csv_field(',', '"').as_deref(), Ok("def"));
+        assert_eq!(tokens.collect::<String>(), "\r\n");
+    }
+
+    #[test]
+    fn parse_csv_field_quoted_separator() {
+        let mut tokens = StrStreamTokens::new("'a;b\nc';d".chars());
+        assert_eq!(tokens.parse_csv_field(';', '\'').as_deref(), Ok("a;b\nc"));
+        assert_eq!(tokens.collect::<String>(), ";d");
+    }
+
+    #[test]
+    fn parse_csv_field_doubled_quote() {
+        let mut tokens = StrStreamTokens::new(r#""""x""""#.chars());
+        assert_eq!(tokens.parse_csv_field(',', '"').as_deref(), Ok(r#""x""#));
+        assert_eq!(tokens.next(), None);
+
+        let mut tokens = StrStreamTokens::new(r#""",x"#.chars());
+        assert_eq!(tokens.parse_csv_field(',', '"').as_deref(), Ok(""));
+        assert_eq!(tokens.collect::<String>(), ",x");
+    }
+
+    #[test]
+    fn parse_csv_field_unterminated() {
+        let mut tokens = StrStreamTokens::new(r#""abc"",d"#.chars());
+        assert_eq!(
+            tokens.parse_csv_field(',', '"'),
+            Err(CsvError::Unterminated)
+        );
+        assert_eq!(tokens.collect::<String>(), r#""abc"",d"#);
+
+        let mut tokens = StrStreamTokens::new(r#""abc"d,e"#.chars());
+        assert_eq!(
+            tokens.parse_csv_field(',', '"'),
+            Err(CsvError::CharAfterQuote('d'))
+        );
+        assert_eq!(tokens.collect::<String>(), r#""abc"d,e"#);
+    }
+}
@@ -0,0 +1,105 @@
+use super::StrStreamTokens;
+use crate::stream_tokens::StreamTokensBuffer;
+use core::{fmt, str::FromStr};
+use yap::Tokens;
+
+/// Error from [`StrStreamTokens::parse_fixed_width`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FixedWidthError<E> {
+    /// Fewer chars than the field's width were available, this many.
+    Truncated(usize),
+    /// The [`FromStr`] impl failed.
+    Parse(E),
+}
+
+impl<E: fmt::Display> fmt::Display for FixedWidthError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixedWidthError::Truncated(n) => write!(f, "field truncated after {n} chars"),
+            FixedWidthError::Parse(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for FixedWidthError<E> {}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator<Item = char>,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// Parse the next `width` chars as a field, such as a zero-padded count, leaving the cursor just after it.
+    ///
+    /// Zeros padding a number are skipped before parsing, so `"000"` parses as `"0"` and `"0042"` as `"42"`.
+    /// The field is parsed from the buffer in place.
+    /// On error nothing is consumed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{FixedWidthError, Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("00000042rest".chars());
+    /// assert_eq!(tokens.parse_fixed_width::<u32>(8), Ok(42));
+    /// assert_eq!(tokens.parse_fixed_width::<u32>(8), Err(FixedWidthError::Truncated(4)));
+    /// assert_eq!(tokens.collect::<String>(), "rest");
+    /// ```
+    pub fn parse_fixed_width<Out>(&mut self, width: usize) -> Result<Out, FixedWidthError<Out::Err>>
+    where
+        Out: FromStr,
+    {
+        let available = self.0.fill(width);
+        if available < width {
+            return Err(FixedWidthError::Truncated(available));
+        }
+        let field = self.buffered_str(self.0.cursor, self.0.cursor + width);
+        // Keep the last zero of an all-zero field, and one before a `.`.
+        let unpadded = field.len() - field.trim_start_matches('0').len();
+        let padding = if field[unpadded..].starts_with(|c: char| c.is_ascii_digit()) {
+            unpadded
+        } else {
+            unpadded.saturating_sub(1)
+        };
+        let res = field[padding..].parse().map_err(FixedWidthError::Parse)?;
+        self.take(width).consume();
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn parse_fixed_width_zero_padded() {
+        let mut tokens = StrStreamTokens::new("0000004200000000000012340.50".chars());
+        assert_eq!(tokens.parse_fixed_width::<u64>(8), Ok(42));
+        assert_eq!(tokens.parse_fixed_width::<u8>(8), Ok(0));
+        assert_eq!(tokens.parse_fixed_width::<u16>(8), Ok(1234));
+        assert_eq!(tokens.parse_fixed_width::<f32>(4), Ok(0.5));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn parse_fixed_width_truncated() {
+        let mut tokens = StrStreamTokens::new("0012".chars());
+        assert_eq!(
+            tokens.parse_fixed_width::<u32>(8),
+            Err(FixedWidthError::Truncated(4))
+        );
+        assert_eq!(tokens.collect::<String>(), "0012");
+    }
+
+    #[test]
+    fn parse_fixed_width_not_numeric() {
+        let mut tokens = StrStreamTokens::new("00x12345".chars());
+        assert!(matches!(
+            tokens.parse_fixed_width::<u32>(4),
+            Err(FixedWidthError::Parse(_))
+        ));
+        // The failed parse consumed nothing, so the next field starts at "00".
+        assert!(matches!(tokens.parse_fixed_width::<u32>(2), Ok(0)));
+        assert_eq!(tokens.collect::<String>(), "x12345");
+    }
+}
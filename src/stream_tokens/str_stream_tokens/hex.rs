@@ -0,0 +1,111 @@
+use super::StrStreamTokens;
+use crate::stream_tokens::StreamTokensBuffer;
+use alloc::vec::Vec;
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StrStreamTokens::decode_hex_into`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexError {
+    /// The run of hex digits ended halfway through a byte.
+    OddLength,
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexError::OddLength => write!(f, "odd number of hex digits"),
+        }
+    }
+}
+
+impl core::error::Error for HexError {}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator<Item = char>,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// Decode pairs of hex digits of either case into `out`, returning how many bytes were appended.
+    ///
+    /// Decoding stops before the first char that isn't a hex digit.
+    /// Only the pair being decoded is buffered, so if the run ends halfway through a pair
+    /// the location is reset to that digit and `out` keeps the bytes decoded before it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("c0ffEE ".chars());
+    /// let mut out = Vec::new();
+    /// assert_eq!(tokens.decode_hex_into(&mut out), Ok(3));
+    /// assert_eq!(out, [0xc0, 0xff, 0xee]);
+    /// assert_eq!(tokens.next(), Some(' '));
+    /// ```
+    pub fn decode_hex_into(&mut self, out: &mut Vec<u8>) -> Result<usize, HexError> {
+        let start_len = out.len();
+        let digit = |c: char| c.to_digit(16);
+        loop {
+            let pair = self.location();
+            let Some(high) = self.0.consume_if(|&c| digit(c).is_some()) else {
+                return Ok(out.len() - start_len);
+            };
+            let Some(low) = self.0.consume_if(|&c| digit(c).is_some()) else {
+                self.set_location(pair);
+                return Err(HexError::OddLength);
+            };
+            let value = |c| digit(c).unwrap_or_default() as u8;
+            out.push(value(high) << 4 | value(low));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::String, vec};
+
+    fn decode(input: &str) -> (Result<Vec<u8>, HexError>, String) {
+        let mut tokens = StrStreamTokens::new(input.chars());
+        let mut out = Vec::new();
+        let res = tokens.decode_hex_into(&mut out).map(|n| {
+            assert_eq!(n, out.len());
+            out
+        });
+        (res, tokens.collect())
+    }
+
+    #[test]
+    fn decode_hex_even_length() {
+        assert_eq!(decode("00ff7F"), (Ok(vec![0x00, 0xff, 0x7f]), "".into()));
+        assert_eq!(
+            decode("DeadBeef"),
+            (Ok(vec![0xde, 0xad, 0xbe, 0xef]), "".into())
+        );
+        assert_eq!(decode(""), (Ok(vec![]), "".into()));
+    }
+
+    #[test]
+    fn decode_hex_odd_trailing_digit() {
+        let mut tokens = StrStreamTokens::new("abcde;".chars());
+        let mut out = Vec::new();
+        assert_eq!(tokens.decode_hex_into(&mut out), Err(HexError::OddLength));
+        // Pairs before the odd digit are kept.
+        assert_eq!(out, [0xab, 0xcd]);
+        assert_eq!(tokens.collect::<String>(), "e;");
+
+        assert_eq!(decode("f"), (Err(HexError::OddLength), "f".into()));
+    }
+
+    #[test]
+    fn decode_hex_stops_at_delimiter() {
+        let mut tokens = StrStreamTokens::new("0a0b:ff0g".chars());
+        let mut out = Vec::new();
+        assert_eq!(tokens.decode_hex_into(&mut out), Ok(2));
+        assert!(tokens.token(':'));
+        assert_eq!(tokens.decode_hex_into(&mut out), Err(HexError::OddLength));
+        assert_eq!(out, [0x0a, 0x0b, 0xff]);
+        assert_eq!(tokens.collect::<String>(), "0g");
+    }
+}
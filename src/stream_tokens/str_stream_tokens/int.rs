@@ -0,0 +1,366 @@
+use super::StrStreamTokens;
+use crate::stream_tokens::StreamTokensBuffer;
+use alloc::vec::Vec;
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StrStreamTokens::parse_i64`] and [`StrStreamTokens::parse_grouped_int`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntParseError {
+    /// There were no digits after the optional sign.
+    NoDigits,
+    /// The value does not fit in the integer type.
+    Overflow,
+    /// A group separator wasn't between two digits.
+    MisplacedSeparator,
+}
+
+impl fmt::Display for IntParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntParseError::NoDigits => write!(f, "expected digits"),
+            IntParseError::Overflow => write!(f, "integer out of range"),
+            IntParseError::MisplacedSeparator => write!(f, "separator not between digits"),
+        }
+    }
+}
+
+impl core::error::Error for IntParseError {}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator<Item = char>,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// Parse an optional `+` or `-` followed by decimal digits as an [`i64`], leaving the cursor just after the digits.
+    /// Digits are accumulated as they are read, so nothing needs to be buffered beyond what live locations require.
+    /// On error the location is reset to where parsing began.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{IntParseError, Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("-42,-".chars());
+    /// assert_eq!(tokens.parse_i64(), Ok(-42));
+    /// assert!(tokens.token(','));
+    /// assert_eq!(tokens.parse_i64(), Err(IntParseError::NoDigits));
+    /// assert_eq!(tokens.collect::<String>(), "-");
+    /// ```
+    pub fn parse_i64(&mut self) -> Result<i64, IntParseError> {
+        let from = self.location();
+        let res = self.parse_i64_inner();
+        // Reset location on error.
+        if res.is_err() {
+            self.set_location(from);
+        }
+        res
+    }
+
+    fn parse_i64_inner(&mut self) -> Result<i64, IntParseError> {
+        let negative = match self.0.consume_one_of(['+', '-']) {
+            Some(sign) => sign == '-',
+            None => false,
+        };
+        // Accumulate towards the sign so that `i64::MIN` doesn't overflow.
+        let mut value = 0i64;
+        let mut digits = 0;
+        while let Some(digit) = self.0.consume_if(char::is_ascii_digit) {
+            let digit = i64::from(digit as u8 - b'0');
+            value = value
+                .checked_mul(10)
+                .and_then(|v| {
+                    if negative {
+                        v.checked_sub(digit)
+                    } else {
+                        v.checked_add(digit)
+                    }
+                })
+                .ok_or(IntParseError::Overflow)?;
+            digits += 1;
+        }
+        if digits == 0 {
+            return Err(IntParseError::NoDigits);
+        }
+        Ok(value)
+    }
+
+    /// Parse an optional `+` or `-` followed by digits in `radix`, such as `1_000_000` or `FF_FF`,
+    /// where single `group_sep`s between digits are skipped. Leaves the cursor just after the last digit.
+    /// On error the location is reset to where parsing began.
+    ///
+    /// The value is accumulated as an [`i128`] and then converted to `Out`.
+    ///
+    /// # Panics
+    ///
+    /// If `radix` is not in `2..=36`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{IntParseError, Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("-1_000 ff_ff 1__0".chars());
+    /// assert_eq!(tokens.parse_grouped_int::<i32>(10, '_'), Ok(-1000));
+    /// assert!(tokens.token(' '));
+    /// assert_eq!(tokens.parse_grouped_int::<u16>(16, '_'), Ok(0xffff));
+    /// assert!(tokens.token(' '));
+    /// assert_eq!(
+    ///     tokens.parse_grouped_int::<u8>(10, '_'),
+    ///     Err(IntParseError::MisplacedSeparator)
+    /// );
+    /// assert_eq!(tokens.collect::<String>(), "1__0");
+    /// ```
+    pub fn parse_grouped_int<Out>(
+        &mut self,
+        radix: u32,
+        group_sep: char,
+    ) -> Result<Out, IntParseError>
+    where
+        Out: TryFrom<i128>,
+    {
+        assert!((2..=36).contains(&radix), "radix must be in 2..=36");
+        let from = self.location();
+        let res = self
+            .parse_grouped_i128(radix, group_sep)
+            .and_then(|value| Out::try_from(value).map_err(|_| IntParseError::Overflow));
+        // Reset location on error.
+        if res.is_err() {
+            self.set_location(from);
+        }
+        res
+    }
+
+    fn parse_grouped_i128(&mut self, radix: u32, group_sep: char) -> Result<i128, IntParseError> {
+        let negative = match self.0.consume_one_of(['+', '-']) {
+            Some(sign) => sign == '-',
+            None => false,
+        };
+        // Accumulate towards the sign so that `i128::MIN` doesn't overflow.
+        let mut value = 0i128;
+        let mut digits = 0;
+        let mut after_sep = false;
+        loop {
+            if let Some(digit) = self.0.consume_if(|c| c.is_digit(radix)) {
+                let digit = i128::from(digit.to_digit(radix).unwrap_or_default());
+                value = value
+                    .checked_mul(i128::from(radix))
+                    .and_then(|v| {
+                        if negative {
+                            v.checked_sub(digit)
+                        } else {
+                            v.checked_add(digit)
+                        }
+                    })
+                    .ok_or(IntParseError::Overflow)?;
+                digits += 1;
+                after_sep = false;
+            } else if self.token(group_sep) {
+                if digits == 0 || after_sep {
+                    return Err(IntParseError::MisplacedSeparator);
+                }
+                after_sep = true;
+            } else {
+                break;
+            }
+        }
+        if after_sep {
+            return Err(IntParseError::MisplacedSeparator);
+        }
+        if digits == 0 {
+            return Err(IntParseError::NoDigits);
+        }
+        Ok(value)
+    }
+
+    /// Append to `out` a list of decimal [`u64`]s separated by `sep`, leaving the cursor just after the last of them.
+    /// Reusing `out` across calls avoids allocating a new list each time.
+    ///
+    /// A `sep` that isn't followed by a number is not consumed, nor is a number that overflows.
+    /// Only the item being parsed is buffered, unless live locations need more.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("1,20,300,x".chars());
+    /// let mut out = Vec::new();
+    /// tokens.parse_number_list_into(&mut out, ',');
+    /// assert_eq!(out, [1, 20, 300]);
+    /// assert_eq!(tokens.collect::<String>(), ",x");
+    /// ```
+    pub fn parse_number_list_into(&mut self, out: &mut Vec<u64>, sep: char) {
+        let Some(first) = self.optional(|t| t.parse_u64()) else {
+            return;
+        };
+        out.push(first);
+        while let Some(n) = self.optional(|t| {
+            if !t.token(sep) {
+                return None;
+            }
+            t.parse_u64()
+        }) {
+            out.push(n);
+        }
+    }
+
+    /// Decimal digits as a [`u64`], or [`None`] if there are none or they overflow.
+    fn parse_u64(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        let mut digits = 0;
+        while let Some(digit) = self.0.consume_if(char::is_ascii_digit) {
+            value = value
+                .checked_mul(10)?
+                .checked_add(u64::from(digit as u8 - b'0'))?;
+            digits += 1;
+        }
+        (digits > 0).then_some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{
+        string::{String, ToString},
+        vec,
+    };
+
+    #[test]
+    fn parse_i64_signs() {
+        for (input, expected, rest) in [("-42", -42, ""), ("+7 ", 7, " "), ("0099x", 99, "x")] {
+            let mut tokens = StrStreamTokens::new(input.chars());
+            assert_eq!(tokens.parse_i64(), Ok(expected));
+            assert_eq!(tokens.collect::<String>(), rest);
+        }
+    }
+
+    #[test]
+    fn parse_i64_no_digits() {
+        for input in ["-", "+", "", "-+1", "x"] {
+            let mut tokens = StrStreamTokens::new(input.chars());
+            assert_eq!(tokens.parse_i64(), Err(IntParseError::NoDigits));
+            assert_eq!(tokens.collect::<String>(), input);
+        }
+    }
+
+    #[test]
+    fn parse_i64_bounds() {
+        let mut tokens = StrStreamTokens::new("9223372036854775807 -9223372036854775808".chars());
+        assert_eq!(tokens.parse_i64(), Ok(i64::MAX));
+        assert!(tokens.token(' '));
+        assert_eq!(tokens.parse_i64(), Ok(i64::MIN));
+    }
+
+    #[test]
+    fn parse_i64_overflow() {
+        for input in [
+            "9223372036854775808",
+            "-9223372036854775809",
+            "123456789012345678901",
+        ] {
+            let mut tokens = StrStreamTokens::new(input.chars());
+            assert_eq!(tokens.parse_i64(), Err(IntParseError::Overflow));
+            assert_eq!(tokens.collect::<String>(), input);
+        }
+    }
+
+    #[test]
+    fn parse_number_list_into_reuses_out() {
+        let mut tokens = StrStreamTokens::new("1;2;3 40;50".chars());
+        let mut out = Vec::new();
+        tokens.parse_number_list_into(&mut out, ';');
+        assert_eq!(out, [1, 2, 3]);
+        let capacity = out.capacity();
+        let ptr = out.as_ptr();
+
+        out.clear();
+        assert!(tokens.token(' '));
+        tokens.parse_number_list_into(&mut out, ';');
+        assert_eq!(out, [40, 50]);
+        assert_eq!(out.capacity(), capacity);
+        assert_eq!(out.as_ptr(), ptr);
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn parse_number_list_into_stops_before_bad_item() {
+        for (input, expected, rest) in [
+            ("", &[][..], ""),
+            ("x", &[], "x"),
+            ("7,", &[7], ","),
+            ("7,,8", &[7], ",,8"),
+            ("7,18446744073709551616", &[7], ",18446744073709551616"),
+        ] {
+            let mut tokens = StrStreamTokens::new(input.chars());
+            let mut out = vec![0];
+            tokens.parse_number_list_into(&mut out, ',');
+            assert_eq!(out[1..], *expected);
+            assert_eq!(tokens.collect::<String>(), rest);
+        }
+    }
+
+    #[test]
+    fn parse_number_list_into_bounded_buffer() {
+        let input = (0..1000)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut tokens = StrStreamTokens::new(input.chars());
+        let mut out = Vec::new();
+        tokens.parse_number_list_into(&mut out, ',');
+        assert_eq!(out.len(), 1000);
+        assert!(tokens.0.peak_buffer_len() < 10);
+    }
+
+    #[test]
+    fn parse_grouped_int_decimal() {
+        for (input, expected, rest) in [
+            ("1_000", 1000, ""),
+            ("1_000_000;", 1_000_000, ";"),
+            ("-12_3", -123, ""),
+            ("42", 42, ""),
+        ] {
+            let mut tokens = StrStreamTokens::new(input.chars());
+            assert_eq!(tokens.parse_grouped_int::<i64>(10, '_'), Ok(expected));
+            assert_eq!(tokens.collect::<String>(), rest);
+        }
+    }
+
+    #[test]
+    fn parse_grouped_int_hex() {
+        let mut tokens = StrStreamTokens::new("DEAD_beef 7'f".chars());
+        assert_eq!(tokens.parse_grouped_int::<u32>(16, '_'), Ok(0xdead_beef));
+        assert!(tokens.token(' '));
+        assert_eq!(tokens.parse_grouped_int::<u8>(16, '\''), Ok(0x7f));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn parse_grouped_int_misplaced_separator() {
+        for input in ["_1", "1__0", "1_", "1_x", "-_1"] {
+            let mut tokens = StrStreamTokens::new(input.chars());
+            assert_eq!(
+                tokens.parse_grouped_int::<i32>(10, '_'),
+                Err(IntParseError::MisplacedSeparator),
+                "{input}"
+            );
+            assert_eq!(tokens.collect::<String>(), input);
+        }
+    }
+
+    #[test]
+    fn parse_grouped_int_errors_rewind() {
+        for (input, err) in [
+            ("", IntParseError::NoDigits),
+            ("-", IntParseError::NoDigits),
+            ("2_56", IntParseError::Overflow),
+            ("-1", IntParseError::Overflow),
+        ] {
+            let mut tokens = StrStreamTokens::new(input.chars());
+            assert_eq!(tokens.parse_grouped_int::<u8>(10, '_'), Err(err), "{input}");
+            assert_eq!(tokens.collect::<String>(), input);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use super::StrStreamTokens;
+use crate::stream_tokens::StreamTokensBuffer;
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StrStreamTokens::parse_ipv4`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IpParseError {
+    /// Digits or a `.` were expected before the fourth octet.
+    MissingOctet,
+    /// An octet was more than 255.
+    OutOfRange,
+    /// The fourth octet was followed by another `.`.
+    TrailingDot,
+}
+
+impl fmt::Display for IpParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IpParseError::MissingOctet => write!(f, "expected 4 octets"),
+            IpParseError::OutOfRange => write!(f, "octet out of range"),
+            IpParseError::TrailingDot => write!(f, "unexpected '.' after 4 octets"),
+        }
+    }
+}
+
+impl core::error::Error for IpParseError {}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator<Item = char>,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// Parse a dotted-quad IPv4 address such as `192.168.0.1`.
+    /// On error the location is reset to where parsing began.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("10.0.0.255:80".chars());
+    /// assert_eq!(tokens.parse_ipv4(), Ok([10, 0, 0, 255]));
+    /// assert_eq!(tokens.collect::<String>(), ":80");
+    /// ```
+    pub fn parse_ipv4(&mut self) -> Result<[u8; 4], IpParseError> {
+        let from = self.location();
+        let res = self.parse_ipv4_inner();
+        // Reset location on error.
+        if res.is_err() {
+            self.set_location(from);
+        }
+        res
+    }
+
+    fn parse_ipv4_inner(&mut self) -> Result<[u8; 4], IpParseError> {
+        let mut octets = [0; 4];
+        for (i, octet) in octets.iter_mut().enumerate() {
+            if i > 0 && !self.token('.') {
+                return Err(IpParseError::MissingOctet);
+            }
+            *octet = self.parse_octet()?;
+        }
+        if self.peek() == Some('.') {
+            return Err(IpParseError::TrailingDot);
+        }
+        Ok(octets)
+    }
+
+    fn parse_octet(&mut self) -> Result<u8, IpParseError> {
+        let mut value = 0u16;
+        let mut digits = 0;
+        while digits < 3 {
+            let Some(digit) = self.0.consume_if(char::is_ascii_digit) else {
+                break;
+            };
+            value = value * 10 + digit as u16 - '0' as u16;
+            digits += 1;
+        }
+        if digits == 0 {
+            return Err(IpParseError::MissingOctet);
+        }
+        // An octet has at most 3 digits, even with leading zeros.
+        if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            return Err(IpParseError::OutOfRange);
+        }
+        u8::try_from(value).map_err(|_| IpParseError::OutOfRange)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn parse_ipv4_valid() {
+        let mut tokens = StrStreamTokens::new("127.0.0.1 0.00.255.9".chars());
+        assert_eq!(tokens.parse_ipv4(), Ok([127, 0, 0, 1]));
+        assert!(tokens.token(' '));
+        assert_eq!(tokens.parse_ipv4(), Ok([0, 0, 255, 9]));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn parse_ipv4_out_of_range() {
+        for input in ["1.2.256.4", "1.2.3.2555", "0255.1.1.1", "1.2.3.0001"] {
+            let mut tokens = StrStreamTokens::new(input.chars());
+            assert_eq!(tokens.parse_ipv4(), Err(IpParseError::OutOfRange));
+            assert_eq!(tokens.collect::<String>(), input);
+        }
+    }
+
+    #[test]
+    fn parse_ipv4_too_few_octets() {
+        for input in ["1.2.3", "1.2..4", "1.2.3.", "x"] {
+            let mut tokens = StrStreamTokens::new(input.chars());
+            assert_eq!(tokens.parse_ipv4(), Err(IpParseError::MissingOctet));
+            assert_eq!(tokens.collect::<String>(), input);
+        }
+    }
+
+    #[test]
+    fn parse_ipv4_trailing_dot() {
+        let mut tokens = StrStreamTokens::new("1.2.3.4.".chars());
+        assert_eq!(tokens.parse_ipv4(), Err(IpParseError::TrailingDot));
+        assert_eq!(tokens.collect::<String>(), "1.2.3.4.");
+    }
+}
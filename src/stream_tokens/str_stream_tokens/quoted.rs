@@ -0,0 +1,127 @@
+use super::StrStreamTokens;
+use crate::stream_tokens::StreamTokensBuffer;
+use alloc::string::String;
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StrStreamTokens::parse_quoted`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuoteError {
+    /// The stream didn't start with the opening quote.
+    NoOpeningQuote,
+    /// The stream ended before the closing quote.
+    Unterminated,
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuoteError::NoOpeningQuote => write!(f, "expected opening quote"),
+            QuoteError::Unterminated => write!(f, "missing closing quote"),
+        }
+    }
+}
+
+impl core::error::Error for QuoteError {}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator<Item = char>,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// Parse a string delimited by `quote`, returning its contents with escapes decoded.
+    ///
+    /// `escape` followed by `n`, `r`, `t` or `0` decodes to the matching control character.
+    /// Followed by anything else, such as `quote` or `escape`, it decodes to that char.
+    /// On error the location is reset to the opening quote.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new(r#""say \"hi\"\n",next"#.chars());
+    /// assert_eq!(tokens.parse_quoted('"', '\\').as_deref(), Ok("say \"hi\"\n"));
+    /// assert_eq!(tokens.collect::<String>(), ",next");
+    /// ```
+    pub fn parse_quoted(&mut self, quote: char, escape: char) -> Result<String, QuoteError> {
+        let from = self.location();
+        let res = self.parse_quoted_inner(quote, escape);
+        // Reset location on error.
+        if res.is_err() {
+            self.set_location(from);
+        }
+        res
+    }
+
+    fn parse_quoted_inner(&mut self, quote: char, escape: char) -> Result<String, QuoteError> {
+        if self.next() != Some(quote) {
+            return Err(QuoteError::NoOpeningQuote);
+        }
+        let mut out = String::new();
+        loop {
+            match self.next().ok_or(QuoteError::Unterminated)? {
+                c if c == quote => return Ok(out),
+                c if c == escape => {
+                    out.push(match self.next().ok_or(QuoteError::Unterminated)? {
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        '0' => '\0',
+                        c => c,
+                    })
+                }
+                c => out.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_quoted_plain() {
+        let mut tokens = StrStreamTokens::new("'hello world' rest".chars());
+        assert_eq!(
+            tokens.parse_quoted('\'', '\\').as_deref(),
+            Ok("hello world")
+        );
+        assert_eq!(tokens.collect::<String>(), " rest");
+    }
+
+    #[test]
+    fn parse_quoted_escapes() {
+        let mut tokens = StrStreamTokens::new(r#""a\"b\\c\td""#.chars());
+        assert_eq!(tokens.parse_quoted('"', '\\').as_deref(), Ok("a\"b\\c\td"));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn parse_quoted_trailing_escape_at_eof() {
+        let mut tokens = StrStreamTokens::new(r#""abc\"#.chars());
+        assert_eq!(
+            tokens.parse_quoted('"', '\\'),
+            Err(QuoteError::Unterminated)
+        );
+        assert_eq!(tokens.collect::<String>(), r#""abc\"#);
+    }
+
+    #[test]
+    fn parse_quoted_unterminated() {
+        let mut tokens = StrStreamTokens::new(r#""abc\""#.chars());
+        assert_eq!(
+            tokens.parse_quoted('"', '\\'),
+            Err(QuoteError::Unterminated)
+        );
+        assert_eq!(tokens.collect::<String>(), r#""abc\""#);
+
+        let mut tokens = StrStreamTokens::new("abc".chars());
+        assert_eq!(
+            tokens.parse_quoted('"', '\\'),
+            Err(QuoteError::NoOpeningQuote)
+        );
+        assert_eq!(tokens.collect::<String>(), "abc");
+    }
+}
@@ -0,0 +1,144 @@
+use super::StrStreamTokens;
+use crate::stream_tokens::StreamTokensBuffer;
+use core::fmt;
+use yap::Tokens;
+
+/// Error from [`StrStreamTokens::skip_trivia`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriviaError {
+    /// The stream ended inside a block comment.
+    UnterminatedComment,
+}
+
+impl fmt::Display for TriviaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriviaError::UnterminatedComment => write!(f, "unterminated block comment"),
+        }
+    }
+}
+
+impl core::error::Error for TriviaError {}
+
+impl<I, Buffer> StrStreamTokens<I, Buffer>
+where
+    I: Iterator<Item = char>,
+    Buffer: StreamTokensBuffer<I::Item> + core::ops::Deref<Target = str>,
+{
+    /// Skip whitespace and comments up to the next significant char.
+    ///
+    /// A `line_comment` runs up to and including the next `\n`, or to the end of the stream.
+    /// Block comments from `block_open` to `block_close` may be nested.
+    /// An empty delimiter disables that kind of comment.
+    /// If the stream ends inside a block comment the location is reset to its start,
+    /// after any trivia before it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{Tokens, StrStreamTokens};
+    ///
+    /// let mut tokens = StrStreamTokens::new("  // note\n /* a /* b */ */ x".chars());
+    /// assert_eq!(tokens.skip_trivia("//", "/*", "*/"), Ok(()));
+    /// assert_eq!(tokens.next(), Some('x'));
+    /// ```
+    pub fn skip_trivia(
+        &mut self,
+        line_comment: &str,
+        block_open: &str,
+        block_close: &str,
+    ) -> Result<(), TriviaError> {
+        loop {
+            self.skip_while(|c| c.is_whitespace());
+            if !line_comment.is_empty() && self.parse_keyword(line_comment) {
+                self.skip_while(|&c| c != '\n');
+                self.next();
+            } else if !block_open.is_empty() && !block_close.is_empty() {
+                let from = self.location();
+                if !self.parse_keyword(block_open) {
+                    return Ok(());
+                }
+                if !self.skip_block_comment(block_open, block_close) {
+                    self.set_location(from);
+                    return Err(TriviaError::UnterminatedComment);
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Skip the rest of a block comment whose `open` was just consumed, returning whether it was closed.
+    fn skip_block_comment(&mut self, open: &str, close: &str) -> bool {
+        let mut depth = 1usize;
+        while depth > 0 {
+            if self.parse_keyword(close) {
+                depth -= 1;
+            } else if self.parse_keyword(open) {
+                depth += 1;
+            } else if self.next().is_none() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    fn skip(input: &str) -> (Result<(), TriviaError>, String) {
+        let mut tokens = StrStreamTokens::new(input.chars());
+        let res = tokens.skip_trivia("//", "/*", "*/");
+        (res, tokens.collect())
+    }
+
+    #[test]
+    fn skip_trivia_mixed() {
+        assert_eq!(
+            skip(" \t\n// one\n\n// two\r\n  /* three */\nlet"),
+            (Ok(()), "let".into())
+        );
+        assert_eq!(skip("/**/x/* y */"), (Ok(()), "x/* y */".into()));
+        assert_eq!(skip("// to the end"), (Ok(()), "".into()));
+        assert_eq!(skip("x // after"), (Ok(()), "x // after".into()));
+        // A lone `/` is significant.
+        assert_eq!(skip(" / 2"), (Ok(()), "/ 2".into()));
+        assert_eq!(skip(""), (Ok(()), "".into()));
+    }
+
+    #[test]
+    fn skip_trivia_nested_block() {
+        assert_eq!(skip("/* a /* b /* c */ */ d */e"), (Ok(()), "e".into()));
+        assert_eq!(skip("/* // */f"), (Ok(()), "f".into()));
+
+        let mut tokens = StrStreamTokens::new("(* a (* b *) *) # c\nlet".chars());
+        assert_eq!(tokens.skip_trivia("#", "(*", "*)"), Ok(()));
+        assert_eq!(tokens.collect::<String>(), "let");
+    }
+
+    #[test]
+    fn skip_trivia_unterminated_block() {
+        assert_eq!(
+            skip("  // c\n/* a /* b */"),
+            (Err(TriviaError::UnterminatedComment), "/* a /* b */".into())
+        );
+        assert_eq!(
+            skip("/*"),
+            (Err(TriviaError::UnterminatedComment), "/*".into())
+        );
+    }
+
+    #[test]
+    fn skip_trivia_disabled_comments() {
+        let mut tokens = StrStreamTokens::new(" # x\n/* y */".chars());
+        assert_eq!(tokens.skip_trivia("#", "", ""), Ok(()));
+        assert_eq!(tokens.collect::<String>(), "/* y */");
+
+        let mut tokens = StrStreamTokens::new(" // x".chars());
+        assert_eq!(tokens.skip_trivia("", "/*", "*/"), Ok(()));
+        assert_eq!(tokens.collect::<String>(), "// x");
+    }
+}
@@ -0,0 +1,160 @@
+use super::{StreamTokens, StreamTokensBuffer, StreamTokensLocation};
+use yap::{TokenLocation, Tokens};
+
+/// The next tokens of a [`StreamTokens`] up to a fixed length, with locations counted from the start of them.
+///
+/// Dropping it skips any of the tokens that weren't consumed, so the parent ends up just after them.
+/// Created by [`StreamTokens::sub_parser`].
+#[derive(Debug)]
+pub struct SubStreamTokens<'a, I: Iterator, Buf>
+where
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    tokens: &'a mut StreamTokens<I, Buf>,
+    start: usize,
+    end: usize,
+}
+
+/// This implements [`TokenLocation`] for [`SubStreamTokens`], with offsets from the start of the sub-stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubStreamTokensLocation {
+    location: StreamTokensLocation,
+    start: usize,
+}
+
+impl TokenLocation for SubStreamTokensLocation {
+    fn offset(&self) -> usize {
+        self.location.offset() - self.start
+    }
+}
+
+impl<I, Buf> Tokens for SubStreamTokens<'_, I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    type Item = I::Item;
+
+    type Location = SubStreamTokensLocation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.tokens.cursor >= self.end {
+            return None;
+        }
+        self.tokens.next()
+    }
+
+    fn location(&self) -> Self::Location {
+        SubStreamTokensLocation {
+            location: self.tokens.location(),
+            start: self.start,
+        }
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        self.tokens.set_location(location.location);
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.tokens.is_at_location(&location.location)
+    }
+}
+
+impl<I, Buf> Drop for SubStreamTokens<'_, I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    fn drop(&mut self) {
+        self.consume();
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Hand the next `len` tokens to a parser as their own [`Tokens`] with locations starting at zero,
+    /// so parsers written for a whole stream can be run over part of one.
+    ///
+    /// Unlike [`Self::take_length_prefixed`] the tokens aren't buffered up front, and there may be fewer if the stream ends.
+    /// Once the sub-stream is dropped the cursor is just after its tokens.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{TokenLocation, Tokens, StreamTokens};
+    ///
+    /// let mut tokens = StreamTokens::new("ab12345cd".chars());
+    /// tokens.take(2).consume();
+    /// {
+    ///     let mut sub = tokens.sub_parser(5);
+    ///     assert_eq!(sub.location().offset(), 0);
+    ///     assert_eq!(sub.parse::<u16, String>(), Ok(12345));
+    /// }
+    /// assert_eq!(tokens.collect::<String>(), "cd");
+    /// ```
+    pub fn sub_parser(&mut self, len: usize) -> SubStreamTokens<'_, I, Buf> {
+        SubStreamTokens {
+            start: self.cursor,
+            end: self.cursor + len,
+            tokens: self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::String, vec::Vec};
+
+    /// Offsets of each `,` in the stream.
+    fn commas(t: &mut impl Tokens<Item = char>) -> Vec<usize> {
+        let mut out = Vec::new();
+        loop {
+            let location = t.location();
+            match t.next() {
+                Some(',') => out.push(location.offset()),
+                Some(_) => {}
+                None => return out,
+            }
+        }
+    }
+
+    #[test]
+    fn sub_parser_window() {
+        let mut tokens = StreamTokens::new("xx,a,b,,yy,".chars());
+        tokens.take(2).consume();
+        let parent = tokens.location();
+        assert_eq!(commas(&mut tokens.sub_parser(5)), [0, 2, 4]);
+        assert_eq!(tokens.location().offset(), parent.offset() + 5);
+        assert_eq!(tokens.collect::<String>(), ",yy,");
+    }
+
+    #[test]
+    fn sub_parser_skips_unconsumed_on_drop() {
+        let mut tokens = StreamTokens::new("abcdefg".chars());
+        {
+            let mut sub = tokens.sub_parser(5);
+            let start = sub.location();
+            assert_eq!(sub.next(), Some('a'));
+            assert_eq!(sub.location().offset(), 1);
+            sub.set_location(start);
+            assert_eq!(sub.take(2).collect::<String>(), "ab");
+        }
+        assert_eq!(tokens.location().offset(), 5);
+        assert_eq!(tokens.collect::<String>(), "fg");
+    }
+
+    #[test]
+    fn sub_parser_stream_ends_first() {
+        let mut tokens = StreamTokens::new("abc".chars());
+        assert_eq!(tokens.sub_parser(5).collect::<String>(), "abc");
+        assert_eq!(tokens.next(), None);
+    }
+}
@@ -0,0 +1,180 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use alloc::vec::Vec;
+use yap::Tokens;
+
+/// Keywords mapped to values for [`StreamTokens::match_trie`].
+///
+/// # Example
+///
+/// ```rust
+/// use yap_streaming::KeywordTrie;
+///
+/// let mut trie = KeywordTrie::new();
+/// trie.insert("let", 0);
+/// let trie: KeywordTrie<_> = [("if", 1), ("in", 2)].into_iter().collect();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeywordTrie<T> {
+    /// The root is the first node.
+    nodes: Vec<Node<T>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Node<T> {
+    /// Sorted by char, with the index of the child node.
+    children: Vec<(char, usize)>,
+    /// Set if a keyword ends here.
+    value: Option<T>,
+}
+
+impl<T> Node<T> {
+    fn child(&self, c: char) -> Option<usize> {
+        let idx = self.children.binary_search_by_key(&c, |&(c, _)| c).ok()?;
+        Some(self.children[idx].1)
+    }
+}
+
+impl<T> Default for KeywordTrie<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> KeywordTrie<T> {
+    /// A trie without any keywords.
+    pub fn new() -> Self {
+        Self {
+            nodes: alloc::vec![Node {
+                children: Vec::new(),
+                value: None,
+            }],
+        }
+    }
+
+    /// Add `keyword`, returning the value it previously had if it was already present.
+    pub fn insert(&mut self, keyword: &str, value: T) -> Option<T> {
+        let mut node = 0;
+        for c in keyword.chars() {
+            node = match self.nodes[node]
+                .children
+                .binary_search_by_key(&c, |&(c, _)| c)
+            {
+                Ok(idx) => self.nodes[node].children[idx].1,
+                Err(idx) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node {
+                        children: Vec::new(),
+                        value: None,
+                    });
+                    self.nodes[node].children.insert(idx, (c, child));
+                    child
+                }
+            };
+        }
+        self.nodes[node].value.replace(value)
+    }
+}
+
+impl<'a, T> FromIterator<(&'a str, T)> for KeywordTrie<T> {
+    fn from_iter<It: IntoIterator<Item = (&'a str, T)>>(iter: It) -> Self {
+        let mut trie = Self::new();
+        for (keyword, value) in iter {
+            trie.insert(keyword, value);
+        }
+        trie
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator<Item = char>,
+    Buf: StreamTokensBuffer<char>,
+{
+    /// Consume the longest keyword in `trie` that the stream continues with, and return its value.
+    /// Otherwise the location is left unchanged.
+    ///
+    /// Each char is only compared once no matter how many keywords there are.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{KeywordTrie, StreamTokens, Tokens};
+    ///
+    /// let trie: KeywordTrie<_> = [("<", 1), ("<=", 2), ("<<=", 3)].into_iter().collect();
+    /// let mut tokens = StreamTokens::new("<<x".chars());
+    /// assert_eq!(tokens.match_trie(&trie), Some(1));
+    /// assert_eq!(tokens.match_trie(&trie), Some(1));
+    /// assert_eq!(tokens.match_trie(&trie), None);
+    /// assert_eq!(tokens.next(), Some('x'));
+    /// ```
+    pub fn match_trie<T: Clone>(&mut self, trie: &KeywordTrie<T>) -> Option<T> {
+        let from = self.location();
+        let mut node = &trie.nodes[0];
+        let mut longest = node.value.as_ref().map(|value| (value, from.clone()));
+        while let Some(child) = self.next().and_then(|c| node.child(c)) {
+            node = &trie.nodes[child];
+            if let Some(value) = &node.value {
+                longest = Some((value, self.location()));
+            }
+        }
+        match longest {
+            Some((value, end)) => {
+                self.set_location(end);
+                Some(value.clone())
+            }
+            None => {
+                self.set_location(from);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, string::String};
+
+    fn keywords() -> KeywordTrie<usize> {
+        let mut trie = KeywordTrie::new();
+        for n in 0..50 {
+            // Keywords that are prefixes of each other, like `k1` and `k10`.
+            assert_eq!(trie.insert(&format!("k{n}"), n), None);
+        }
+        trie
+    }
+
+    #[test]
+    fn match_trie_longest() {
+        let trie = keywords();
+        let mut tokens = StreamTokens::new("k4k49k499k5".chars());
+        assert_eq!(tokens.match_trie(&trie), Some(4));
+        assert_eq!(tokens.match_trie(&trie), Some(49));
+        assert_eq!(tokens.match_trie(&trie), Some(49));
+        assert_eq!(tokens.next(), Some('9'));
+        assert_eq!(tokens.match_trie(&trie), Some(5));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn match_trie_rewinds() {
+        let trie = keywords();
+        for input in ["k", "kx", "x1", "", "K1"] {
+            let mut tokens = StreamTokens::new(input.chars());
+            assert_eq!(tokens.match_trie(&trie), None);
+            assert_eq!(tokens.collect::<String>(), input);
+        }
+    }
+
+    #[test]
+    fn insert_replaces() {
+        let mut trie = KeywordTrie::new();
+        assert_eq!(trie.insert("ab", 1), None);
+        assert_eq!(trie.insert("ab", 2), Some(1));
+        // The empty keyword matches without consuming anything.
+        trie.insert("", 0);
+        let mut tokens = StreamTokens::new("ac".chars());
+        assert_eq!(tokens.match_trie(&trie), Some(0));
+        assert_eq!(tokens.collect::<String>(), "ac");
+    }
+}
@@ -0,0 +1,132 @@
+use super::{StreamTokens, StreamTokensBuffer};
+use core::fmt;
+use yap::Tokens;
+
+/// [`Tokens`] skipping whitespace before every item of a [`StreamTokens`], returned by [`StreamTokens::skipping_whitespace`].
+///
+/// Locations are those of the wrapped tokens, so they include any whitespace that hasn't been skipped yet.
+pub struct WsSkipping<T, F> {
+    tokens: T,
+    is_ws: F,
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for WsSkipping<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WsSkipping")
+            .field("tokens", &self.tokens)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, F> WsSkipping<T, F> {
+    /// The wrapped tokens.
+    pub fn into_inner(self) -> T {
+        self.tokens
+    }
+}
+
+impl<T, F> Tokens for WsSkipping<T, F>
+where
+    T: Tokens,
+    F: FnMut(&T::Item) -> bool,
+{
+    type Item = T::Item;
+
+    type Location = T::Location;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tokens.skip_while(&mut self.is_ws);
+        self.tokens.next()
+    }
+
+    fn location(&self) -> Self::Location {
+        self.tokens.location()
+    }
+
+    fn set_location(&mut self, location: Self::Location) {
+        self.tokens.set_location(location);
+    }
+
+    fn is_at_location(&self, location: &Self::Location) -> bool {
+        self.tokens.is_at_location(location)
+    }
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Skip items matching `is_ws` before every item, so parsers built on [`Tokens`] needn't skip whitespace themselves.
+    ///
+    /// Since whitespace is skipped between every item, it can't separate multi-item tokens such as numbers;
+    /// `"1 2"` reads the same as `"12"`. Use [`WsSkipping::into_inner`] to parse those without skipping.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yap_streaming::{StreamTokens, Tokens};
+    ///
+    /// let mut tokens = StreamTokens::new(" a\tb ".chars()).skipping_whitespace(|c| c.is_whitespace());
+    /// assert!(tokens.tokens("ab".chars()));
+    /// assert_eq!(tokens.next(), None);
+    /// ```
+    pub fn skipping_whitespace<F>(self, is_ws: F) -> WsSkipping<Self, F>
+    where
+        F: FnMut(&I::Item) -> bool,
+    {
+        WsSkipping {
+            tokens: self,
+            is_ws,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yap::TokenLocation;
+
+    fn digit(t: &mut impl Tokens<Item = char>) -> Option<u32> {
+        t.optional(|t| t.next()?.to_digit(10))
+    }
+
+    fn op(t: &mut impl Tokens<Item = char>) -> Option<char> {
+        t.optional(|t| t.next().filter(|c| matches!(c, '+' | '-')))
+    }
+
+    /// Sum of single digits added or subtracted, with no whitespace handling of its own.
+    fn eval(t: &mut impl Tokens<Item = char>) -> Option<i64> {
+        let mut total = i64::from(digit(t)?);
+        while let Some(op) = op(t) {
+            let n = i64::from(digit(t)?);
+            total += if op == '+' { n } else { -n };
+        }
+        Some(total)
+    }
+
+    #[test]
+    fn arithmetic_without_explicit_skips() {
+        let mut tokens = StreamTokens::new("  1  +  2".chars()).skipping_whitespace(|c| *c == ' ');
+        assert_eq!(eval(&mut tokens), Some(3));
+        assert_eq!(tokens.next(), None);
+
+        let mut tokens =
+            StreamTokens::new("9 -\t3+ 1 ;".chars()).skipping_whitespace(|c| c.is_whitespace());
+        assert_eq!(eval(&mut tokens), Some(7));
+        assert_eq!(tokens.next(), Some(';'));
+    }
+
+    #[test]
+    fn locations_map_to_stream() {
+        let mut tokens = StreamTokens::new("a   b".chars()).skipping_whitespace(|c| *c == ' ');
+        assert_eq!(tokens.next(), Some('a'));
+        let before = tokens.location();
+        assert_eq!(before.offset(), 1);
+        assert_eq!(tokens.next(), Some('b'));
+        assert_eq!(tokens.location().offset(), 5);
+        tokens.set_location(before);
+        assert_eq!(tokens.into_inner().next(), Some(' '));
+    }
+}
@@ -0,0 +1,108 @@
+//! Helpers for fuzzing [`StreamTokens`] by driving it with a script of [`Op`]s
+//! and comparing against [`apply_ops_reference`], which never drops anything.
+use crate::{stream_tokens::StreamTokensBuffer, StreamTokens};
+use alloc::vec::Vec;
+use yap::Tokens;
+
+/// An operation on [`StreamTokens`] for [`StreamTokens::apply_ops`].
+///
+/// Indexes refer to the list of saved locations. Operations with an index past the end of that list do nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    /// Call [`Tokens::next`].
+    Next,
+    /// Save a [`Tokens::location`] to the end of the list.
+    SaveLoc,
+    /// Call [`Tokens::set_location`] with a copy of the saved location.
+    RestoreLoc(usize),
+    /// Remove the saved location from the list and drop it.
+    DropLoc(usize),
+}
+
+impl<I, Buf> StreamTokens<I, Buf>
+where
+    I: Iterator,
+    I::Item: Clone,
+    Buf: StreamTokensBuffer<I::Item>,
+{
+    /// Run each of `ops` in order, returning what each [`Op::Next`] returned.
+    pub fn apply_ops(&mut self, ops: &[Op]) -> Vec<Option<I::Item>> {
+        let mut saved = Vec::new();
+        let mut out = Vec::new();
+        for op in ops {
+            match *op {
+                Op::Next => out.push(self.next()),
+                Op::SaveLoc => saved.push(self.location()),
+                Op::RestoreLoc(idx) => {
+                    if let Some(loc) = saved.as_slice().get(idx) {
+                        self.set_location(loc.clone());
+                    }
+                }
+                Op::DropLoc(idx) => {
+                    if idx < saved.len() {
+                        saved.remove(idx);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// What [`StreamTokens::apply_ops`] should return, computed by indexing directly into `items`.
+pub fn apply_ops_reference<Item: Clone>(items: &[Item], ops: &[Op]) -> Vec<Option<Item>> {
+    let mut cursor = 0;
+    let mut saved = Vec::new();
+    let mut out = Vec::new();
+    for op in ops {
+        match *op {
+            Op::Next => {
+                out.push(items.get(cursor).cloned());
+                cursor = (cursor + 1).min(items.len());
+            }
+            Op::SaveLoc => saved.push(cursor),
+            Op::RestoreLoc(idx) => {
+                if let Some(&loc) = saved.as_slice().get(idx) {
+                    cursor = loc;
+                }
+            }
+            Op::DropLoc(idx) => {
+                if idx < saved.len() {
+                    saved.remove(idx);
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzz_style_matches_reference() {
+        let items = (0..200u8).collect::<Vec<_>>();
+        // Deterministic xorshift standing in for fuzzer input.
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        for _ in 0..100 {
+            let ops = (0..300)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    let idx = (state >> 8) as usize % 4;
+                    match state % 5 {
+                        0 | 1 => Op::Next,
+                        2 => Op::SaveLoc,
+                        3 => Op::RestoreLoc(idx),
+                        _ => Op::DropLoc(idx),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mut tokens = StreamTokens::from_vec(items.clone());
+            assert_eq!(tokens.apply_ops(&ops), apply_ops_reference(&items, &ops));
+        }
+    }
+}